@@ -1,46 +1,644 @@
 use std::{
+    ffi::OsString,
     fmt,
-    fs::File,
-    io::{stdin, BufRead, BufReader},
-    path::PathBuf,
+    fs::{self, File},
+    io::{self, stdin, BufRead, BufReader, BufWriter, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
     str::FromStr,
+    sync::mpsc,
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::Context;
 
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 
 use clap_verbosity_flag::Verbosity;
 
+use flate2::bufread::MultiGzDecoder;
+
+use ip::concrete::Afi;
+
 use simple_logger::SimpleLogger;
 
-use crate::ir::RoaPrefixRanges;
+use crate::ir::{
+    check_roa_order, dedup_sorted, group_by_asn, normalize_slashes, parse_asn_text_with,
+    parse_numbered_text_with, parse_vrp_csv_with, stream_issues, trim_list_markers, CanonicalForm,
+    DiffLine, HostBits, Issue, RoaOrderIssue, RoaPrefixRange, RoaPrefixRanges,
+};
 
 /// Entry-point for `roasort` application.
 #[allow(clippy::missing_errors_doc)]
-pub fn main() -> anyhow::Result<()> {
-    let mut ret = Ok(());
-    let args = Cli::parse();
+pub fn main() -> anyhow::Result<ExitCode> {
+    let args = Cli::parse().into_options();
     SimpleLogger::new()
         .with_level(args.verbosity.log_level_filter())
         .init()?;
-    let input = args.input.reader()?;
-    args.input_type
-        .read(input)?
+    check_output_type(&args)?;
+    let mut profile = Profile::new(args.profile);
+    // checked before any input is opened, since detecting the input type
+    // already reads from stdin
+    if args.stdin_timeout > 0
+        && (args.stdin || args.input.iter().any(|input| matches!(input, Input::StdIn)))
+        && io::stdin().is_terminal()
+        && !input_ready(|| stdin().lock(), Duration::from_secs(args.stdin_timeout))
+    {
+        eprintln!("Hint: reading from stdin; pass a file or pipe input, or use --help");
+        return Ok(ExitCode::FAILURE);
+    }
+    let inputs = open_inputs(&args)?;
+    let in_place_path = in_place_path(&args, &inputs)?;
+    let mut rewritten = Vec::new();
+    let mut writer = args.output.writer()?;
+    let mut discarded = io::sink();
+    // `check` prints only its findings, of which `--validate` writes to the
+    // output
+    let discard = args.check_only;
+    #[cfg(feature = "json")]
+    let discard = discard && !args.validate;
+    let inner: &mut dyn Write = if discard {
+        &mut discarded
+    } else if in_place_path.is_some() {
+        &mut rewritten
+    } else {
+        &mut writer
+    };
+    let mut terminated = LineEndingWriter {
+        inner,
+        line_ending: args.line_ending,
+    };
+    let out: &mut dyn Write = &mut terminated;
+    if args.assume_sorted {
+        return assume_sorted(&args, inputs, out, &mut profile);
+    }
+    if args.stream_check {
+        return stream_check(&args, inputs, &mut profile);
+    }
+    if args.compare {
+        return compare(&args, inputs, out, &mut profile);
+    }
+    if args.check_roa_internal_order {
+        return check_roa_internal_order(inputs, &mut profile);
+    }
+    if args.group_by_asn {
+        return write_asn_groups(&args, inputs, out, &mut profile);
+    }
+    let (ranges, wire_order) = read_merged(&args, inputs)?;
+    profile.phase("read, parse and sort");
+    if args.count {
+        eprintln!("Count: {}", ranges.stats());
+    }
+    let asn = output_asn(&args, &ranges)?;
+    if args.first_diff {
+        return Ok(ranges.first_difference().map_or(ExitCode::SUCCESS, |diff| {
+            eprintln!("Error: {diff}");
+            ExitCode::FAILURE
+        }));
+    }
+    let issues: Vec<_> = ranges
+        .validate_in(args.canonical_form.into())
         .into_iter()
-        .enumerate()
-        .for_each(|(i, (item, j))| {
-            if i != j {
-                ret = Err(anyhow::anyhow!("input was mis-ordered"));
+        .map(|issue| (issue, args.severity(&issue)))
+        .filter(|(_, severity)| *severity != Severity::Ignore)
+        .collect();
+    #[cfg(feature = "json")]
+    if args.validate {
+        ReportFormat::Jsonl.report(out, &wire_order, &issues)?;
+        out.flush()?;
+        profile.phase("validate");
+        return Ok(exit_code(&issues));
+    }
+    if args.diff {
+        print_canonical_diff(&ranges);
+    }
+    profile.phase("validate");
+    let ranges = transform(&args, ranges)?;
+    write_summaries(&args, &ranges, out)?;
+    let over_expanded = report_findings(&args, &ranges)?;
+    profile.phase("transform");
+    write_items(&args, ranges, asn, out)?;
+    out.flush()?;
+    if let Some(path) = in_place_path {
+        rewrite(&path, &rewritten)
+            .with_context(|| format!("failed to rewrite {}", path.display()))?;
+    }
+    args.report_format
+        .report(&mut io::stderr(), &wire_order, &issues)?;
+    profile.phase("output");
+    if over_expanded
+        && args.fail_on_expansion
+        && issues
+            .iter()
+            .all(|(_, severity)| *severity != Severity::Error)
+    {
+        return Ok(ExitCode::from(5));
+    }
+    Ok(exit_code(&issues))
+}
+
+/// Check the options that only apply to some types of output.
+fn check_output_type(args: &Options) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !args.annotate_coverage || matches!(args.output_type, OutputType::Text),
+        "--annotate-coverage is only supported for text output"
+    );
+    anyhow::ensure!(
+        !args.show_asn || matches!(args.output_type, OutputType::Text | OutputType::Tsv),
+        "--show-asn is only supported for text and TSV output"
+    );
+    anyhow::ensure!(
+        !args.check_only || !(args.fix || matches!(args.output, Output::File(_))),
+        "check prints only the issues found, so --fix and --output cannot be used with it; \
+         use sort instead"
+    );
+    Ok(())
+}
+
+/// An input, with its type and a reader for its content.
+type Source = (Input, InputType, Box<dyn BufRead>);
+
+/// Open each input, and infer or detect its type where not given.
+fn open_inputs(args: &Options) -> anyhow::Result<Vec<Source>> {
+    if args.stdin {
+        log::info!("--stdin given, ignoring input paths");
+        vec![Input::StdIn]
+    } else {
+        args.input.clone()
+    }
+    .into_iter()
+    .map(|input| {
+        let mut reader = input.reader()?;
+        let input_type = match args.input_type.unwrap_or_else(|| input.inferred_type()) {
+            InputType::Auto => InputType::detect(&mut reader)?,
+            input_type => input_type,
+        };
+        Ok((input, input_type, reader))
+    })
+    .collect()
+}
+
+/// Check the inputs against the options that depend on them, returning the
+/// path of the file to rewrite with `--in-place`, if given.
+fn in_place_path(args: &Options, inputs: &[Source]) -> anyhow::Result<Option<PathBuf>> {
+    let roa_input = inputs
+        .iter()
+        .any(|(_, input_type, _)| matches!(input_type, InputType::Roa));
+    anyhow::ensure!(!args.verify || roa_input, "--verify requires ROA input");
+    let in_place_path = match inputs {
+        _ if !args.in_place => None,
+        [(input @ Input::File(path), InputType::Text, _)] if !input.is_gzipped() => {
+            Some(path.clone())
+        }
+        _ => anyhow::bail!("--in-place requires an uncompressed text input file, and only one"),
+    };
+    if let Output::File(output_path) = &args.output {
+        anyhow::ensure!(
+            !inputs
+                .iter()
+                .any(|(input, ..)| matches!(input, Input::File(path) if path == output_path)),
+            "output would overwrite the input file; use --fix --in-place to rewrite it"
+        );
+    }
+    Ok(in_place_path)
+}
+
+/// Output the items of already sorted text input as they are read, for
+/// `--assume-sorted`.
+fn assume_sorted(
+    args: &Options,
+    inputs: Vec<Source>,
+    out: &mut dyn Write,
+    profile: &mut Profile,
+) -> anyhow::Result<ExitCode> {
+    let Ok([(_, InputType::Text, input)]) = <[_; 1]>::try_from(inputs) else {
+        anyhow::bail!("--assume-sorted is only supported for text input, from a single source");
+    };
+    anyhow::ensure!(
+        args.output_type.is_streamable(),
+        "--assume-sorted is not supported for {:?} output",
+        args.output_type
+    );
+    let options = args.read_options();
+    let items = parse_numbered_text_with(text_lines(input, options)?, options.host_bits);
+    let mut items: Box<dyn Iterator<Item = _>> = if args.no_dedup {
+        Box::new(items.map(|item| item.map(|(_, item)| item)))
+    } else {
+        Box::new(dedup_sorted(items))
+    };
+    items.try_for_each(|item| {
+        if let Some(line) = args.render(&item?) {
+            writeln!(out, "{line}")?;
+        }
+        Ok::<_, anyhow::Error>(())
+    })?;
+    out.flush()?;
+    profile.phase("read, parse and output");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Validate text input as it is read, for `--stream-check`.
+fn stream_check(
+    args: &Options,
+    inputs: Vec<Source>,
+    profile: &mut Profile,
+) -> anyhow::Result<ExitCode> {
+    let Ok([(_, InputType::Text, input)]) = <[_; 1]>::try_from(inputs) else {
+        anyhow::bail!("--stream-check is only supported for text input, from a single source");
+    };
+    let mut issues = Vec::new();
+    let options = args.read_options();
+    for issue in stream_issues(
+        parse_numbered_text_with(text_lines(input, options)?, options.host_bits),
+        args.canonical_form.into(),
+    ) {
+        let issue = issue?;
+        let severity = args.severity(&issue);
+        if severity != Severity::Ignore {
+            issues.push((issue, severity));
+        }
+        if severity == Severity::Error {
+            break;
+        }
+    }
+    args.report_format.report(&mut io::stderr(), &[], &issues)?;
+    profile.phase("read, parse and validate");
+    Ok(exit_code(&issues))
+}
+
+/// Output the items removed and added between two inputs, for `diff`.
+fn compare(
+    args: &Options,
+    inputs: Vec<Source>,
+    out: &mut dyn Write,
+    profile: &mut Profile,
+) -> anyhow::Result<ExitCode> {
+    let Ok([old, new]) = <[_; 2]>::try_from(inputs) else {
+        anyhow::bail!("diff requires exactly two inputs");
+    };
+    let [old, new] = [old, new].map(|(input, input_type, reader)| {
+        let mut ranges = input_type
+            .read(reader, args.read_options())
+            .with_context(|| format!("failed to read input {input}"))?;
+        if let Some(afi) = args.afi {
+            ranges.retain_afi(afi.into());
+        }
+        Ok::<_, anyhow::Error>(ranges)
+    });
+    let (removed, added) = old?.symmetric_difference(&new?);
+    for item in &removed {
+        writeln!(out, "-{item}")?;
+    }
+    for item in &added {
+        writeln!(out, "+{item}")?;
+    }
+    out.flush()?;
+    profile.phase("read and compare");
+    Ok(if removed.is_empty() && added.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    })
+}
+
+/// Check the wire order of each ROA input, for
+/// `--check-roa-internal-order`.
+fn check_roa_internal_order(
+    inputs: Vec<Source>,
+    profile: &mut Profile,
+) -> anyhow::Result<ExitCode> {
+    let mut canonical = true;
+    for (input, input_type, mut reader) in inputs {
+        anyhow::ensure!(
+            matches!(input_type, InputType::Roa),
+            "--check-roa-internal-order requires ROA input, but {input} is not"
+        );
+        let mut buf = Vec::new();
+        _ = reader.read_to_end(&mut buf)?;
+        for issue in
+            check_roa_order(&buf).with_context(|| format!("failed to read input {input}"))?
+        {
+            eprintln!("Error: {input}: {issue}");
+            canonical = false;
+        }
+    }
+    profile.phase("read and check");
+    Ok(if canonical {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(IssueKind::Misorder.exit_code())
+    })
+}
+
+/// Output the items of all inputs grouped by origin AS, for
+/// `--group-by-asn`.
+fn write_asn_groups(
+    args: &Options,
+    inputs: Vec<Source>,
+    out: &mut dyn Write,
+    profile: &mut Profile,
+) -> anyhow::Result<ExitCode> {
+    anyhow::ensure!(
+        matches!(args.output_type, OutputType::Text),
+        "--group-by-asn is only supported for text output"
+    );
+    let mut items = Vec::new();
+    let options = args.read_options();
+    for (input, input_type, reader) in inputs {
+        if matches!(input_type, InputType::Text) {
+            items.extend(parse_asn_text_with(
+                text_lines(reader, options)?,
+                options.host_bits,
+            ));
+        } else if matches!(input_type, InputType::VrpCsv) {
+            items.extend(parse_vrp_csv_with(
+                text_lines(reader, options)?,
+                options.host_bits,
+            ));
+        } else {
+            let ranges = input_type.read(reader, options)?;
+            let asn = ranges
+                .as_id()
+                .with_context(|| format!("input {input} has no origin AS number"))?;
+            items.extend(ranges.into_iter().map(|(item, _)| Ok((asn, item))));
+        }
+    }
+    if let Some(afi) = args.afi.map(Afi::from) {
+        items.retain(|item| !matches!(item, Ok((_, item)) if item.afi() != afi));
+    }
+    for (asn, mut ranges) in group_by_asn(items)? {
+        if args.aggregate {
+            ranges.aggregate()?;
+        }
+        writeln!(out, "AS{asn}:")?;
+        for (item, _) in ranges {
+            if let Some(line) = args.render(&item) {
+                writeln!(out, "{line}")?;
             }
-            if item.has_explicit_equal_max_length() {
-                ret = Err(anyhow::anyhow!(
-                    "item {item} has unnecessarily specified max_length"
-                ));
+        }
+    }
+    out.flush()?;
+    profile.phase("read, group and output");
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Read and merge all inputs, together with any departures from canonical
+/// wire order found in ROA input with `--strict`.
+fn read_merged(
+    args: &Options,
+    inputs: Vec<Source>,
+) -> anyhow::Result<(RoaPrefixRanges, Vec<(Input, RoaOrderIssue)>)> {
+    let count = inputs.len();
+    // the wire order of each ROA is checked as it is read, since it is lost
+    // once the items are merged into canonical order
+    let mut wire_order = Vec::new();
+    let mut sources = inputs.into_iter().map(|(input, input_type, mut reader)| {
+        if args.strict && matches!(input_type, InputType::Roa) {
+            let mut buf = Vec::new();
+            _ = reader.read_to_end(&mut buf)?;
+            wire_order.extend(
+                check_roa_order(&buf)
+                    .with_context(|| format!("failed to read input {input}"))?
+                    .into_iter()
+                    .map(|issue| (input.clone(), issue)),
+            );
+            reader = Box::new(io::Cursor::new(buf));
+        }
+        let ranges = input_type.read(reader, args.read_options());
+        if let (true, Ok(ranges)) = (args.show_signing_time, &ranges) {
+            match ranges.signing_time() {
+                Some(time) => eprintln!("{input}: signed at {}", time.to_rfc3339()),
+                None => eprintln!("{input}: signing time unknown"),
             }
-            println!("{item}");
+        }
+        if count > 1 {
+            ranges.with_context(|| format!("failed to read input {input}"))
+        } else {
+            ranges
+        }
+    });
+    let mut ranges = sources.next().context("no input given")??;
+    for source in sources {
+        ranges.merge(source?);
+    }
+    if matches!(args.implicit_maxlength, ImplicitMaxLength::Host) {
+        ranges.expand_implicit_max_length();
+    }
+    if let Some(afi) = args.afi {
+        ranges.retain_afi(afi.into());
+    }
+    Ok((ranges, wire_order))
+}
+
+/// The origin AS number to output with each item, if any.
+fn output_asn(args: &Options, ranges: &RoaPrefixRanges) -> anyhow::Result<Option<u32>> {
+    Ok(if args.show_asn {
+        Some(
+            ranges
+                .as_id()
+                .context("--show-asn requires ROA input, which carries an origin AS number")?,
+        )
+    } else if matches!(args.output_type, OutputType::VrpCsv) {
+        Some(args.asn.or_else(|| ranges.as_id()).context(
+            "VRP CSV output requires an origin AS number: give --asn, or use ROA or VRP CSV input",
+        )?)
+    } else if matches!(args.output_type, OutputType::Tsv) {
+        args.asn.or_else(|| ranges.as_id())
+    } else {
+        None
+    })
+}
+
+/// Print a unified diff from the input order to the canonical order, for
+/// `--diff`, unless the input is already canonical.
+fn print_canonical_diff(ranges: &RoaPrefixRanges) {
+    let diff = ranges.canonical_diff();
+    if diff
+        .iter()
+        .all(|line| matches!(line, DiffLine::Unchanged(_)))
+    {
+        return;
+    }
+    let count = |added: bool| {
+        diff.iter()
+            .filter(|line| match line {
+                DiffLine::Unchanged(_) => true,
+                DiffLine::Removed(_) => !added,
+                DiffLine::Added(_) => added,
+            })
+            .count()
+    };
+    eprintln!("--- input");
+    eprintln!("+++ canonical");
+    eprintln!("@@ -1,{} +1,{} @@", count(false), count(true));
+    diff.iter().for_each(|line| eprintln!("{line}"));
+}
+
+/// Fold, aggregate and exclude items as the options require.
+fn transform(args: &Options, mut ranges: RoaPrefixRanges) -> anyhow::Result<RoaPrefixRanges> {
+    match args.dedup_key {
+        DedupKey::PrefixMaxlength => {}
+        DedupKey::Prefix => {
+            ranges.fold_max_length().iter().for_each(|(item, kept)| {
+                eprintln!("Warning: folded {item} into {kept}, changing the set of entries");
+            });
+        }
+    }
+    if args.aggregate {
+        ranges.aggregate()?;
+    }
+    if let Some(path) = &args.exclude_file {
+        let exclude = Input::File(path.clone());
+        let excluded = exclude
+            .inferred_type()
+            .read(exclude.reader()?, args.read_options())
+            .context("failed to read exclusions")?;
+        ranges = if args.exclude_covered {
+            ranges.uncovered_by(&excluded)
+        } else {
+            ranges.difference(&excluded)
+        };
+    }
+    Ok(ranges)
+}
+
+/// Write the summaries of the items that precede or replace them: the
+/// `--afi-summary` lines, and `count-json` or `summary-table` output.
+fn write_summaries(
+    args: &Options,
+    ranges: &RoaPrefixRanges,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    if args.afi_summary {
+        for summary in ranges.afi_summary() {
+            writeln!(out, "{summary}")?;
+        }
+    }
+    #[cfg(feature = "json")]
+    if matches!(args.output_type, OutputType::CountJson) {
+        writeln!(out, "{}", serde_json::to_string(&ranges.stats())?)?;
+    }
+    if matches!(args.output_type, OutputType::SummaryTable) {
+        write_summary_table(out, ranges)?;
+    }
+    Ok(())
+}
+
+/// Report overlapping items, items that could be aggregated, and items that
+/// authorize more levels of more-specifics than `--max-expansion`, as the
+/// options require, returning whether there were any of the last.
+fn report_findings(args: &Options, ranges: &RoaPrefixRanges) -> anyhow::Result<bool> {
+    if args.report_overlaps {
+        for (covering, covered) in ranges.overlaps() {
+            eprintln!("Overlap: {covering} covers {covered}");
+        }
+    }
+    if args.warn_nonaggregatable {
+        ranges
+            .aggregation_candidates()?
+            .iter()
+            .for_each(|(parent, count)| {
+                eprintln!("Warning: {count} entries could be aggregated into {parent}");
+            });
+    }
+    let Some(limit) = args.max_expansion else {
+        return Ok(false);
+    };
+    let label = if args.fail_on_expansion {
+        "Error"
+    } else {
+        "Warning"
+    };
+    let mut found = false;
+    for item in ranges.exceeding_expansion(limit) {
+        eprintln!(
+            "{label}: item {item} authorizes {} levels of more-specifics, more than {limit}",
+            item.more_specific_levels()
+        );
+        found = true;
+    }
+    Ok(found)
+}
+
+/// Write the items in the order and form that the options require.
+fn write_items(
+    args: &Options,
+    ranges: RoaPrefixRanges,
+    asn: Option<u32>,
+    out: &mut dyn Write,
+) -> anyhow::Result<()> {
+    // validation above is always against the canonical order, so that the
+    // presentation order can be changed without affecting the result
+    let mut output: Vec<_> = if args.no_dedup {
+        ranges
+            .with_duplicates()
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect()
+    } else {
+        ranges.into_iter().map(|(item, _)| item).collect()
+    };
+    let canonical_form = CanonicalForm::from(args.canonical_form);
+    output.sort_by(|a, b| canonical_form.compare(a, b));
+    args.output_sort_key.sort(&mut output);
+    if let Some(n) = args.sample {
+        let seed = args.seed.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| {
+                    elapsed.as_secs() ^ u64::from(elapsed.subsec_nanos())
+                })
         });
-    ret
+        output = sample(output, n, seed);
+    }
+    if matches!(args.output_type, OutputType::VrpCsv) && !args.afi_summary {
+        writeln!(out, "ASN,IP Prefix,Max Length")?;
+    }
+    #[cfg(feature = "json")]
+    let mut json_items = Vec::new();
+    for (i, item) in output.into_iter().enumerate() {
+        if args.warn_afi_mismatch && item.is_ipv4_embedded() {
+            eprintln!("Warning: item {item} looks like an IPv4 prefix written as IPv6");
+        }
+        if args.warn_open_maxlength && item.has_open_max_length() {
+            eprintln!("Warning: item {item} authorizes all more-specifics down to host routes");
+        }
+        if !args.afi_summary
+            && i >= args.skip
+            && args.limit.is_none_or(|limit| i - args.skip < limit)
+        {
+            #[cfg(feature = "json")]
+            if matches!(args.output_type, OutputType::Json) {
+                json_items.push(item);
+                continue;
+            }
+            if let Some(line) = args.render(&item) {
+                match (asn, args.output_type) {
+                    (Some(asn), OutputType::VrpCsv) => writeln!(out, "AS{asn},{line}")?,
+                    (Some(asn), OutputType::Tsv) => writeln!(out, "AS{asn}\t{line}")?,
+                    (Some(asn), _) => writeln!(out, "AS{asn} {line}")?,
+                    (None, _) => writeln!(out, "{line}")?,
+                }
+            }
+        }
+    }
+    #[cfg(feature = "json")]
+    if matches!(args.output_type, OutputType::Json) {
+        writeln!(out, "{}", serde_json::to_string(&json_items)?)?;
+    }
+    Ok(())
+}
+
+/// The process exit status for the issues found: that of the kind of error
+/// with the lowest status, or success if there are no errors.
+fn exit_code(issues: &[(Issue, Severity)]) -> ExitCode {
+    issues
+        .iter()
+        .filter(|(_, severity)| *severity == Severity::Error)
+        .map(|(issue, _)| IssueKind::from(issue).exit_code())
+        .min()
+        .map_or(ExitCode::SUCCESS, ExitCode::from)
 }
 
 const ABOUT: &str = "
@@ -49,22 +647,691 @@ then sort and de-duplicate the elements according to the canonicalization
 process described in `draft-ietf-sidrops-rfc6482bis`.
 ";
 
+const EXIT_STATUS: &str = "\
+Exit status:
+  0  the input is canonical, or has only warnings
+  1  the input could not be read or parsed, or another error occurred
+  2  an entry is mis-ordered
+  3  an entry is duplicated
+  4  an entry has a redundant max_length
+  5  an entry exceeds --max-expansion, with --fail-on-expansion
+
+If errors of several kinds are found, the lowest of their statuses is used.";
+
 /// Order and deduplicate ROA IP address information.
 #[derive(Debug, Parser)]
-#[command(author, version, about, long_about = ABOUT)]
+#[command(
+    author,
+    version,
+    about,
+    long_about = ABOUT,
+    after_help = EXIT_STATUS,
+    args_conflicts_with_subcommands = true,
+)]
 struct Cli {
-    /// Path to input data file
-    #[arg(default_value_t = Input::StdIn)]
-    input: Input,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    #[command(flatten)]
+    options: Options,
+}
+
+impl Cli {
+    /// The options given, adjusted for the subcommand, if any.
+    ///
+    /// Without a subcommand, the input is checked as by `check`, but the
+    /// canonical form is also printed, as it was before subcommands were
+    /// added.
+    fn into_options(self) -> Options {
+        let mut options = match self.command {
+            None => self.options,
+            Some(Command::Check(mut options)) => {
+                options.check_only = true;
+                options
+            }
+            Some(Command::Sort(mut options)) => {
+                options.fix = true;
+                options
+            }
+            Some(Command::Convert {
+                from,
+                to,
+                mut options,
+            }) => {
+                options.input_type = Some(from);
+                options.output_type = to;
+                options.fix = true;
+                options
+            }
+            Some(Command::Diff(mut options)) => {
+                options.compare = true;
+                options
+            }
+        };
+        if options.fold_maxlength {
+            options.dedup_key = DedupKey::Prefix;
+        }
+        options
+    }
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Check that the input is canonical, exiting with an error status if
+    /// not, and print only the issues found.
+    ///
+    /// Without a subcommand, the input is checked in the same way, but the
+    /// canonical form is also printed.
+    #[command(after_help = EXIT_STATUS)]
+    Check(Options),
+    /// Print the canonical form, exiting successfully even if the input was
+    /// not canonical. The same as `check --fix`.
+    Sort(Options),
+    /// Convert the input from one type to another, in canonical form.
+    Convert {
+        /// Input type
+        #[arg(long, value_enum, conflicts_with = "input_type")]
+        from: InputType,
+
+        /// Output type
+        #[arg(long, value_enum, conflicts_with = "output_type")]
+        to: OutputType,
+
+        #[command(flatten)]
+        options: Options,
+    },
+    /// Compare the entries of two inputs, printing those only in the first
+    /// prefixed with `-`, then those only in the second prefixed with `+`,
+    /// each in canonical order. Exits with an error status if they differ.
+    Diff(Options),
+}
+
+#[derive(Debug, clap::Args)]
+#[allow(clippy::struct_excessive_bools)]
+struct Options {
+    /// Paths to input data files.
+    ///
+    /// Reads from stdin if omitted or `-`. A path beginning with `-` can be
+    /// given after a `--` separator. The entries of several inputs are
+    /// merged, in the order given, before they are sorted.
+    #[arg(default_values_t = [Input::StdIn])]
+    input: Vec<Input>,
+
+    /// Path to write output to, creating or truncating it.
+    ///
+    /// Writes to stdout if omitted or `-`.
+    #[arg(
+        long,
+        short = 'o',
+        value_name = "PATH",
+        default_value_t = Output::StdOut,
+        conflicts_with = "in_place",
+    )]
+    output: Output,
+
+    /// Line terminator of the output
+    #[arg(long, value_enum, default_value_t = LineEnding::Lf)]
+    line_ending: LineEnding,
+
+    /// Read from stdin, even if input paths are given.
+    ///
+    /// This takes precedence over the input paths, which in turn take
+    /// precedence over the default of reading from stdin.
+    #[arg(long)]
+    stdin: bool,
+
+    /// Seconds to wait for input when reading from an interactive terminal,
+    /// before exiting with a hint instead.
+    ///
+    /// 0, the default, waits forever. Piped input is never timed out.
+    #[arg(long, value_name = "SECS", default_value_t = 0)]
+    stdin_timeout: u64,
+
+    /// Input type.
+    ///
+    /// If given, this applies to every input. Otherwise, it is inferred from
+    /// the extension of each input file, looking through a `.gz` extension:
+    /// `.txt` for text, `.roa` or `.pem` for ROA, `.json` for JSON (with the
+    /// `json` feature), `.rpsl` for RPSL and `.csv` for VRP CSV input.
+    /// Failing that, ROA input is detected from its content, and anything
+    /// else is read as text.
+    #[arg(long, short = 't', value_enum)]
+    input_type: Option<InputType>,
+
+    /// Check that the eContent of ROA input matches the message digest
+    /// signed by the ROA's signer.
+    ///
+    /// This detects a modified eContent, but the signature itself, and the
+    /// certificate that it is made with, are not verified.
+    #[arg(long)]
+    verify: bool,
+
+    /// Output type
+    #[arg(long, visible_alias = "format", value_enum, default_value_t = OutputType::Text)]
+    output_type: OutputType,
+
+    /// Origin AS number, for VRP CSV or TSV output of input that does not
+    /// carry one, such as text
+    #[arg(long, value_name = "N")]
+    asn: Option<u32>,
+
+    /// Canonical ordering against which input is validated and in which
+    /// entries are emitted
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = CanonicalFormArg::Draft,
+        conflicts_with_all = ["assume_sorted", "first_diff"],
+    )]
+    canonical_form: CanonicalFormArg,
+
+    /// Keep only the entries in this address family, discarding the others
+    /// before validation, so that ordering is only checked among the
+    /// entries kept
+    #[arg(long, value_enum, conflicts_with_all = ["assume_sorted", "stream_check"])]
+    afi: Option<AfiArg>,
+
+    /// Order in which to emit entries.
+    ///
+    /// Input is always validated against the canonical order: this only
+    /// re-orders the output after validation.
+    #[arg(long, value_enum, default_value_t = OutputSortKey::Canonical)]
+    output_sort_key: OutputSortKey,
+
+    /// Severity of each kind of validation issue.
+    ///
+    /// Given as a comma-separated list of `KIND=SEVERITY` pairs, where `KIND`
+    /// is one of `misorder`, `redundant` or `duplicate`, and `SEVERITY` is
+    /// one of `error` (the default), `warn` or `ignore`. Only errors cause
+    /// a non-zero exit status.
+    #[arg(long, value_delimiter = ',', value_name = "KIND=SEVERITY")]
+    severity: Vec<SeverityRule>,
+
+    /// Format of the validation report written to stderr
+    #[arg(long, value_enum, default_value_t = ReportFormat::Human)]
+    report_format: ReportFormat,
+
+    /// Only validate the input, printing a JSON object for each issue found,
+    /// one per line, instead of the canonical entries.
+    ///
+    /// Nothing is printed if the input is canonical. The exit status is as
+    /// without `--fix`.
+    #[cfg(feature = "json")]
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "report_format", "fix", "assume_sorted", "stream_check", "group_by_asn",
+            "first_diff",
+        ],
+    )]
+    validate: bool,
+
+    /// Assume that the input is already sorted, and only de-duplicate it.
+    ///
+    /// Input is processed in a single streaming pass, failing at the first
+    /// entry found to be out of order. Only text input is supported.
+    #[arg(long, conflicts_with_all = ["afi_summary", "limit", "skip", "output_sort_key"])]
+    assume_sorted: bool,
+
+    /// Check that the input is canonical in a single streaming pass, without
+    /// printing the canonical entries.
+    ///
+    /// Each entry is compared only with the one before it, and checking
+    /// stops at the first error, so memory use does not grow with the size
+    /// of the input. Only text input is supported.
+    #[arg(
+        long,
+        conflicts_with_all = ["assume_sorted", "fix", "first_diff", "diff", "group_by_asn"],
+    )]
+    stream_check: bool,
+
+    /// Check only that the structure of each ROA input was already in
+    /// canonical order, without printing the canonical entries.
+    ///
+    /// The `ipAddrBlocks` must be ordered by address family, with one block
+    /// for each, and the `addresses` of each block must be in canonical
+    /// order. The first departure in each block is reported.
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "assume_sorted", "stream_check", "fix", "first_diff", "diff", "group_by_asn",
+        ],
+    )]
+    check_roa_internal_order: bool,
+
+    /// Compare two inputs, rather than canonicalizing them. Set by the
+    /// `diff` subcommand.
+    #[arg(skip)]
+    compare: bool,
+
+    /// Key on which entries are considered duplicates of each other.
+    ///
+    /// With `prefix`, of the entries sharing a prefix only the one with the
+    /// greatest max_length is kept, since it authorizes everything that the
+    /// others do. This is a semantic normalization rather than a
+    /// canonicalization: the output no longer contains the same set of
+    /// entries as the input, so a warning is printed for each entry folded.
+    /// Validation is still against the input as given.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = DedupKey::PrefixMaxlength,
+        conflicts_with = "assume_sorted",
+    )]
+    dedup_key: DedupKey,
+
+    /// Keep duplicate entries, outputting each in sorted order after the
+    /// entry it duplicates.
+    ///
+    /// Duplicates are then not reported as issues: only mis-ordering and
+    /// redundant max_length values are.
+    #[arg(
+        long,
+        conflicts_with_all = ["dedup_key", "fold_maxlength", "aggregate", "group_by_asn"],
+    )]
+    no_dedup: bool,
+
+    /// Keep only the entry with the greatest max_length for each prefix.
+    ///
+    /// The same as `--dedup-key prefix`.
+    #[arg(long, conflicts_with_all = ["assume_sorted", "dedup_key"])]
+    fold_maxlength: bool,
+
+    /// Reduce the entries to a minimal set authorizing the same routes.
+    ///
+    /// Entries covered by another entry are removed, and an entry together
+    /// with both of its children is merged into one with a greater
+    /// max_length. Like `--dedup-key prefix`, this changes the set of entries,
+    /// and validation is still against the input as given. With
+    /// `--group-by-asn`, each origin AS is aggregated separately.
+    #[arg(long, conflicts_with_all = ["assume_sorted", "stream_check"])]
+    aggregate: bool,
+
+    /// Remove the entries listed in this file from the output.
+    ///
+    /// Entries must match exactly, unless `--exclude-covered` is also given.
+    /// Validation is still against the input as given.
+    #[arg(long, value_name = "PATH", conflicts_with = "assume_sorted")]
+    exclude_file: Option<PathBuf>,
+
+    /// Also remove entries that are covered by an entry in the exclusion
+    /// file, i.e. whose routes it authorizes
+    #[arg(long, requires = "exclude_file")]
+    exclude_covered: bool,
+
+    /// Report only the first entry that deviates from the canonical order,
+    /// together with the entry expected in its place, and exit without
+    /// printing the canonical entries
+    #[arg(long, conflicts_with = "assume_sorted")]
+    first_diff: bool,
+
+    /// When the input is not in canonical order, print a unified diff from
+    /// the input order to the canonical order to stderr
+    #[arg(long)]
+    diff: bool,
 
-    /// Input type
-    #[arg(long, short = 't', value_enum, default_value_t = InputType::Text)]
-    input_type: InputType,
+    /// Print a summary of the input to stderr: the number of distinct entries
+    /// in each address family, of duplicates removed, and of entries with an
+    /// explicit max_length
+    #[arg(long, conflicts_with_all = ["assume_sorted", "stream_check", "group_by_asn"])]
+    count: bool,
+
+    /// Print the count and first/last entries per address family instead of
+    /// the canonical entries
+    #[arg(long)]
+    afi_summary: bool,
+
+    /// Print at most this many entries, in canonical order
+    #[arg(long, value_name = "N")]
+    limit: Option<usize>,
+
+    /// Skip this many entries, in canonical order, before printing
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    skip: usize,
+
+    /// Print the canonical form, exiting successfully even if the input was
+    /// not canonical.
+    ///
+    /// Mis-ordered, duplicate and redundant max_length entries are all
+    /// corrected by canonicalization, so none of them is reported.
+    #[arg(long, conflicts_with_all = ["assume_sorted", "first_diff"])]
+    fix: bool,
+
+    /// With `--fix`, rewrite the input file with the canonical form instead
+    /// of printing it. Only supported for text input files
+    #[arg(long, requires = "fix", conflicts_with = "afi_summary")]
+    in_place: bool,
+
+    // set by the `check` subcommand, to print nothing but the issues found
+    #[arg(skip)]
+    check_only: bool,
+
+    /// Print this many entries, sampled uniformly at random from the whole
+    /// set, in output order
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["limit", "skip", "afi_summary", "assume_sorted"],
+    )]
+    sample: Option<usize>,
+
+    /// Seed for `--sample`, to make the sample reproducible.
+    ///
+    /// The same seed always selects the same entries from the same input,
+    /// across platforms and versions. If omitted, a seed is taken from the
+    /// system clock.
+    #[arg(long, requires = "sample")]
+    seed: Option<u64>,
+
+    /// Apply stricter conformance checks.
+    ///
+    /// For ROA input, a warning is reported for each ROA whose encoding was
+    /// not already in canonical order, as by `--check-roa-internal-order`.
+    /// For all other input, a prefix with host bits set, such as
+    /// `10.0.0.5/8`, is rejected rather than masked with a warning.
+    #[arg(long)]
+    strict: bool,
+
+    /// Keep explicitly specified max_length values that are equal to the
+    /// prefix length as written, instead of flagging them as redundant.
+    ///
+    /// This is for downstream parsers that require the explicit form, such
+    /// as `192.0.2.0/24-24`, in text, TSV and JSON lines output.
+    #[arg(long, visible_alias = "keep-redundant-maxlength")]
+    no_implicit_collapse: bool,
+
+    /// Meaning of an entry without an explicit max_length.
+    ///
+    /// With `host`, it is taken to authorize all more-specifics down to host
+    /// routes, as some tooling does, for ordering and output alike. An
+    /// explicit max_length equal to the prefix length is then not redundant,
+    /// and is kept.
+    #[arg(
+        long,
+        value_enum,
+        default_value_t = ImplicitMaxLength::Prefix,
+        conflicts_with_all = ["assume_sorted", "stream_check", "group_by_asn"],
+    )]
+    implicit_maxlength: ImplicitMaxLength,
+
+    /// Append to each entry a comment with the number of more-specific
+    /// prefix lengths that it authorizes (text output only)
+    #[arg(long)]
+    annotate_coverage: bool,
+
+    /// Prefix each entry with the origin AS number of the ROA, as `AS<n>`
+    /// (ROA input, and text or TSV output only)
+    #[arg(long)]
+    show_asn: bool,
+
+    /// Group the entries by origin AS, in order of AS number, each group
+    /// headed by an `AS<n>:` line (text output only).
+    ///
+    /// The origin AS of each entry of text input is given by an `AS<n>`
+    /// prefix, as in `AS65000 192.0.2.0/24`, or by a preceding line holding
+    /// only `AS<n>:`. The entries of each group are canonicalized, but not
+    /// validated.
+    #[arg(long, conflicts_with_all = ["assume_sorted", "first_diff", "fix", "show_asn"])]
+    group_by_asn: bool,
+
+    /// Print the signing time of each ROA input to stderr, as an RFC 3339
+    /// timestamp, or `unknown` if it is not given
+    #[arg(long)]
+    show_signing_time: bool,
+
+    /// Warn about entries that look like they were written in the wrong
+    /// address family.
+    ///
+    /// This is a heuristic, and only covers IPv6 entries: it flags IPv6
+    /// prefixes that embed an IPv4 address (IPv4-mapped or IPv4-compatible,
+    /// other than `::` and `::1`). IPv4 entries are not checked, since an
+    /// IPv4 prefix length beyond 32 is already rejected.
+    #[arg(long)]
+    warn_afi_mismatch: bool,
+
+    /// Warn about entries whose max_length extends to the maximum for the
+    /// address family (32 or 128), which is likely over-permissive
+    #[arg(long)]
+    warn_open_maxlength: bool,
+
+    /// Warn about entries whose max_length exceeds the prefix length by more
+    /// than this many bits, authorizing more levels of more-specifics than
+    /// is likely intended
+    #[arg(long, value_name = "N")]
+    max_expansion: Option<u8>,
+
+    /// Treat entries exceeding `--max-expansion` as errors rather than
+    /// warnings
+    #[arg(long, requires = "max_expansion")]
+    fail_on_expansion: bool,
+
+    /// Warn about runs of sibling prefixes that could be aggregated into a
+    /// common parent.
+    ///
+    /// This is advisory only: the suggested parent also authorizes the
+    /// intermediate prefix lengths.
+    #[arg(long)]
+    warn_nonaggregatable: bool,
+
+    /// Report each pair of entries in which one covers the other, i.e.
+    /// authorizes every route that the other does, on stderr.
+    ///
+    /// The printed entries are unaffected.
+    #[arg(long)]
+    report_overlaps: bool,
+
+    /// Strip list bullets (`-`, `*`, `•`) and trailing commas from each line
+    /// of text input before parsing
+    #[arg(long)]
+    trim_list_markers: bool,
+
+    /// Replace Unicode look-alikes of `/` (fullwidth solidus, fraction slash
+    /// and division slash) with `/` in each line of text input before parsing
+    #[arg(long)]
+    normalize_slashes: bool,
+
+    /// Print the time taken by each processing phase to stderr.
+    ///
+    /// Parsing and sorting happen in a single pass over the input, and are
+    /// timed together with reading it.
+    #[arg(long)]
+    profile: bool,
 
     #[command(flatten)]
     verbosity: Verbosity,
 }
 
+impl Options {
+    fn render(&self, item: &RoaPrefixRange) -> Option<String> {
+        let line = self.output_type.format(item, self.keeps_explicit_equal())?;
+        if self.annotate_coverage {
+            let levels = item.more_specific_levels();
+            let plural = if levels == 1 { "" } else { "s" };
+            Some(format!("{line}  # {levels} more-specific level{plural}"))
+        } else {
+            Some(line)
+        }
+    }
+
+    /// Whether an explicit max_length equal to the prefix length is kept,
+    /// rather than flagged as redundant and collapsed.
+    const fn keeps_explicit_equal(&self) -> bool {
+        self.no_implicit_collapse || matches!(self.implicit_maxlength, ImplicitMaxLength::Host)
+    }
+
+    const fn read_options(&self) -> ReadOptions {
+        ReadOptions {
+            trim_list_markers: self.trim_list_markers,
+            normalize_slashes: self.normalize_slashes,
+            host_bits: if self.strict {
+                HostBits::Reject
+            } else {
+                HostBits::Mask(warn_masked_host_bits)
+            },
+            verify_roa: self.verify,
+        }
+    }
+
+    fn severity(&self, issue: &Issue) -> Severity {
+        let kind = IssueKind::from(issue);
+        if self.fix
+            || (kind == IssueKind::Redundant && self.keeps_explicit_equal())
+            || (kind == IssueKind::Duplicate && self.no_dedup)
+        {
+            return Severity::Ignore;
+        }
+        self.severity
+            .iter()
+            .rev()
+            .find(|rule| rule.kind == kind)
+            .map_or(Severity::Error, |rule| rule.severity)
+    }
+}
+
+/// Wait up to `timeout` for input from the source returned by `open`,
+/// returning whether any arrived, or the source was closed.
+///
+/// The source is opened and read in a separate thread, which is left blocked
+/// if the wait times out. Input is peeked at, not consumed: for stdin, it
+/// stays in the buffer of the global handle, to be read as usual afterwards.
+fn input_ready<F, R>(open: F, timeout: Duration) -> bool
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: BufRead,
+{
+    let (tx, rx) = mpsc::channel();
+    _ = thread::spawn(move || {
+        _ = open().fill_buf().map(|_| ());
+        _ = tx.send(());
+    });
+    rx.recv_timeout(timeout).is_ok()
+}
+
+/// Write a table of summary statistics about `ranges`, with the labels and
+/// values aligned in columns.
+fn write_summary_table(out: &mut dyn Write, ranges: &RoaPrefixRanges) -> io::Result<()> {
+    let stats = ranges.stats();
+    let redundant = ranges
+        .validate()
+        .iter()
+        .filter(|issue| matches!(issue, Issue::RedundantMaxLength { .. }))
+        .count();
+    let mut rows = vec![
+        ("total entries", stats.total.to_string()),
+        ("ipv4 entries", stats.ipv4.to_string()),
+        ("ipv6 entries", stats.ipv6.to_string()),
+        ("duplicates removed", stats.duplicates_removed.to_string()),
+        ("redundant max_length", redundant.to_string()),
+        ("covered entries", ranges.covered_count().to_string()),
+    ];
+    for (afi, min_label, max_label) in [
+        (
+            Afi::Ipv4,
+            "ipv4 min prefix length",
+            "ipv4 max prefix length",
+        ),
+        (
+            Afi::Ipv6,
+            "ipv6 min prefix length",
+            "ipv6 max prefix length",
+        ),
+    ] {
+        let histogram = ranges.prefix_length_histogram(afi);
+        let length = |entry: Option<(&u8, &usize)>| {
+            entry.map_or_else(|| "-".to_string(), |(length, _)| length.to_string())
+        };
+        rows.push((min_label, length(histogram.first_key_value())));
+        rows.push((max_label, length(histogram.last_key_value())));
+    }
+    let label_width = rows.iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+    let value_width = rows.iter().map(|(_, value)| value.len()).max().unwrap_or(0);
+    rows.iter().try_for_each(|(label, value)| {
+        writeln!(out, "{label:<label_width$}  {value:>value_width$}")
+    })
+}
+
+/// Wall-clock timing of consecutive processing phases.
+#[derive(Debug)]
+struct Profile {
+    enabled: bool,
+    last: Instant,
+}
+
+impl Profile {
+    fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            last: Instant::now(),
+        }
+    }
+
+    /// Mark the end of the phase `name`, which started at the end of the
+    /// previous phase.
+    fn phase(&mut self, name: &str) {
+        if self.enabled {
+            eprintln!("Profile: {name}: {:?}", self.last.elapsed());
+        }
+        self.last = Instant::now();
+    }
+}
+
+/// Select `n` items uniformly at random, in one pass, using reservoir
+/// sampling. The selected items are returned in their original order.
+fn sample<T, I>(iter: I, n: usize, seed: u64) -> Vec<T>
+where
+    I: IntoIterator<Item = T>,
+{
+    let mut rng = SplitMix64(seed);
+    let mut reservoir = Vec::with_capacity(n);
+    iter.into_iter().enumerate().for_each(|(i, item)| {
+        if i < n {
+            reservoir.push((i, item));
+        } else if let Some(slot) = usize::try_from(rng.below(i as u64 + 1))
+            .ok()
+            .and_then(|j| reservoir.get_mut(j))
+        {
+            *slot = (i, item);
+        }
+    });
+    reservoir.sort_unstable_by_key(|(i, _)| *i);
+    reservoir.into_iter().map(|(_, item)| item).collect()
+}
+
+/// A small, fast pseudo-random number generator.
+///
+/// This is used in place of an external crate so that the output of
+/// `--sample` for a given `--seed` is stable.
+#[derive(Debug)]
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A value in `0..bound`.
+    #[allow(clippy::cast_possible_truncation)]
+    fn below(&mut self, bound: u64) -> u64 {
+        // the high half of the product is always less than `bound`
+        ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+    }
+}
+
+/// Replace the contents of the file at `path`, via a temporary file in the
+/// same directory, so that the file is never left partially written.
+fn rewrite(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut tmp_name = OsString::from(".");
+    tmp_name.push(path.file_name().unwrap_or_default());
+    tmp_name.push(".roasort.tmp");
+    let tmp = path.with_file_name(tmp_name);
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
 #[derive(Debug, Clone)]
 enum Input {
     StdIn,
@@ -72,7 +1339,9 @@ enum Input {
 }
 
 impl Input {
-    fn reader(self) -> anyhow::Result<Box<dyn BufRead>> {
+    /// Open the input, transparently decompressing it if the path ends in
+    /// `.gz` or the content starts with the gzip magic bytes.
+    fn reader(&self) -> anyhow::Result<Box<dyn BufRead>> {
         log::info!("opening input");
         let reader: Box<dyn BufRead> = match self {
             Self::StdIn => Box::new(stdin().lock()),
@@ -82,7 +1351,64 @@ impl Input {
                 Box::new(BufReader::new(file))
             }
         };
-        Ok(reader)
+        if self.is_gzipped() {
+            log::info!("decompressing gzip input");
+            Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+        } else {
+            Ok(decompress(reader)?)
+        }
+    }
+
+    fn is_gzipped(&self) -> bool {
+        matches!(self, Self::File(path) if path.extension().is_some_and(|ext| ext == "gz"))
+    }
+}
+
+impl Input {
+    fn inferred_type(&self) -> InputType {
+        let Self::File(path) = self else {
+            return InputType::Auto;
+        };
+        // look through a `.gz` extension to that of the compressed content
+        let extension = if self.is_gzipped() {
+            path.file_stem().map(Path::new).and_then(Path::extension)
+        } else {
+            path.extension()
+        };
+        match extension {
+            Some(ext) if ext == "txt" => {
+                log::info!("inferred text input from extension of {}", path.display());
+                InputType::Text
+            }
+            Some(ext) if ext == "roa" => {
+                log::info!("inferred ROA input from extension of {}", path.display());
+                InputType::Roa
+            }
+            Some(ext) if ext == "pem" => {
+                log::info!(
+                    "inferred PEM-armored ROA input from extension of {}",
+                    path.display()
+                );
+                InputType::Roa
+            }
+            #[cfg(feature = "json")]
+            Some(ext) if ext == "json" => {
+                log::info!("inferred JSON input from extension of {}", path.display());
+                InputType::Json
+            }
+            Some(ext) if ext == "rpsl" => {
+                log::info!("inferred RPSL input from extension of {}", path.display());
+                InputType::Rpsl
+            }
+            Some(ext) if ext == "csv" => {
+                log::info!(
+                    "inferred VRP CSV input from extension of {}",
+                    path.display()
+                );
+                InputType::VrpCsv
+            }
+            _ => InputType::Auto,
+        }
     }
 }
 
@@ -109,22 +1435,562 @@ impl FromStr for Input {
     }
 }
 
+#[derive(Debug, Clone)]
+enum Output {
+    StdOut,
+    File(PathBuf),
+}
+
+impl Output {
+    fn writer(&self) -> anyhow::Result<Box<dyn Write>> {
+        log::info!("opening output");
+        let writer: Box<dyn Write> = match self {
+            Self::StdOut => Box::new(io::stdout().lock()),
+            Self::File(path) => {
+                log::info!("trying to create {}", path.display());
+                let file = File::create(path)
+                    .with_context(|| format!("failed to create output file {}", path.display()))?;
+                Box::new(BufWriter::new(file))
+            }
+        };
+        Ok(writer)
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum LineEnding {
+    /// Unix line endings, `\n`
+    Lf,
+    /// Windows line endings, `\r\n`
+    Crlf,
+}
+
+/// A writer that ends each line written to it with the chosen terminator.
+struct LineEndingWriter<W> {
+    inner: W,
+    line_ending: LineEnding,
+}
+
+impl<W: Write> Write for LineEndingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.line_ending {
+            LineEnding::Lf => self.inner.write(buf),
+            // write either the text before the first newline, or the newline
+            // alone, so that the count returned is always of bytes of `buf`
+            LineEnding::Crlf => match buf.iter().position(|&b| b == b'\n') {
+                Some(0) => self.inner.write_all(b"\r\n").map(|()| 1),
+                Some(end) => self.inner.write(&buf[..end]),
+                None => self.inner.write(buf),
+            },
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl fmt::Display for Output {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StdOut => write!(f, "STDOUT"),
+            Self::File(path) => path.to_string_lossy().fmt(f),
+        }
+    }
+}
+
+impl FromStr for Output {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "-" || s == "STDOUT" {
+            Ok(Self::StdOut)
+        } else {
+            s.parse()
+                .map(Self::File)
+                .context("failed to parse output file path")
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, ValueEnum)]
 enum InputType {
     Text,
     Roa,
+    #[cfg(feature = "json")]
+    Json,
+    /// The prefixes of RPSL `route:` and `route6:` objects, each with an
+    /// implicit max_length
+    Rpsl,
+    /// Validated ROA Payload CSV, as exported by RPKI validators, with
+    /// `ASN,IP Prefix,Max Length` columns
+    VrpCsv,
+    /// ROA if the input starts like a DER-encoded ROA, otherwise text
+    Auto,
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+fn decompress(mut reader: Box<dyn BufRead>) -> io::Result<Box<dyn BufRead>> {
+    // peek at the buffered input, so that nothing is consumed from a
+    // non-seekable source before choosing a decoder
+    if reader.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        log::info!("decompressing gzip input");
+        Ok(Box::new(BufReader::new(MultiGzDecoder::new(reader))))
+    } else {
+        Ok(reader)
+    }
+}
+
+/// Options controlling how each input is read: clean-ups applied to each
+/// line of text input before parsing, the handling of prefixes with host
+/// bits set, and checks on ROA input.
+#[derive(Debug, Clone, Copy)]
+struct ReadOptions {
+    trim_list_markers: bool,
+    normalize_slashes: bool,
+    host_bits: HostBits,
+    verify_roa: bool,
+}
+
+fn warn_masked_host_bits(prefix: &str, masked: &str) {
+    eprintln!("Warning: masked host bits of {prefix}, giving {masked}");
+}
+
+impl ReadOptions {
+    fn apply(self, line: String) -> String {
+        let line = if self.normalize_slashes {
+            normalize_slashes(&line).into_owned()
+        } else {
+            line
+        };
+        if self.trim_list_markers {
+            trim_list_markers(&line).to_string()
+        } else {
+            line
+        }
+    }
+}
+
+fn text_lines(
+    reader: Box<dyn BufRead>,
+    options: ReadOptions,
+) -> io::Result<impl Iterator<Item = io::Result<String>>> {
+    Ok(reader
+        .lines()
+        .map(move |line| line.map(|line| options.apply(line))))
 }
 
 impl InputType {
-    fn read<R: BufRead>(self, mut reader: R) -> anyhow::Result<RoaPrefixRanges> {
+    /// Distinguish a DER-encoded ROA from text by peeking at the start of
+    /// the input, without consuming it.
+    ///
+    /// A ROA starts with a SEQUENCE tag (`0x30`, which is also ASCII `0`)
+    /// followed by a long-form length, which is never valid text. Encoded
+    /// in base64, those octets start with `MI`, which is never a valid
+    /// prefix either, and PEM armor starts with `-----BEGIN`.
+    fn detect(reader: &mut dyn BufRead) -> io::Result<Self> {
+        match reader.fill_buf()? {
+            [0x30, 0x80..=0x84, ..] | [b'M', b'I', ..] => {
+                log::info!("detected ROA input from its content");
+                Ok(Self::Roa)
+            }
+            buf if buf.starts_with(b"-----BEGIN") => {
+                log::info!("detected PEM-armored ROA input from its content");
+                Ok(Self::Roa)
+            }
+            _ => Ok(Self::Text),
+        }
+    }
+
+    fn read(
+        self,
+        mut reader: Box<dyn BufRead>,
+        options: ReadOptions,
+    ) -> anyhow::Result<RoaPrefixRanges> {
         match self {
-            Self::Text => RoaPrefixRanges::from_text(reader.lines()),
+            Self::Text => {
+                RoaPrefixRanges::from_text_with(text_lines(reader, options)?, options.host_bits)
+            }
+            Self::Auto => Self::detect(&mut reader)?.read(reader, options),
             Self::Roa => {
                 let mut buf = Vec::new();
                 log::info!("reading input");
                 _ = reader.read_to_end(&mut buf)?;
-                RoaPrefixRanges::from_roa(&buf)
+                if options.verify_roa {
+                    RoaPrefixRanges::from_verified_roa(&buf)
+                } else {
+                    RoaPrefixRanges::from_roa(&buf)
+                }
             }
+            #[cfg(feature = "json")]
+            Self::Json => {
+                let mut buf = Vec::new();
+                log::info!("reading input");
+                _ = reader.read_to_end(&mut buf)?;
+                RoaPrefixRanges::from_json_with(&buf, options.host_bits)
+            }
+            Self::Rpsl => {
+                RoaPrefixRanges::from_rpsl_with(text_lines(reader, options)?, options.host_bits)
+            }
+            Self::VrpCsv => {
+                RoaPrefixRanges::from_vrp_csv_with(text_lines(reader, options)?, options.host_bits)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputType {
+    /// One canonical entry per line
+    Text,
+    /// Tab-separated prefix and max_length columns, preceded by an `AS<n>`
+    /// column if the origin AS number is known
+    Tsv,
+    /// One JSON object per line
+    #[cfg(feature = "json")]
+    Jsonl,
+    /// A single JSON array of entry objects
+    #[cfg(feature = "json")]
+    Json,
+    /// A single JSON object counting the entries, instead of the entries
+    #[cfg(feature = "json")]
+    CountJson,
+    /// A table of summary statistics, instead of the entries
+    SummaryTable,
+    /// Validated ROA Payload CSV, with a header and `ASN,IP Prefix,Max Length`
+    /// columns
+    VrpCsv,
+}
+
+impl OutputType {
+    /// Whether entries can be written as they are read, rather than only
+    /// once they have all been read.
+    const fn is_streamable(self) -> bool {
+        #[cfg(feature = "json")]
+        if matches!(self, Self::Json | Self::CountJson) {
+            return false;
+        }
+        !matches!(self, Self::SummaryTable | Self::VrpCsv)
+    }
+
+    /// Format a single entry, or `None` if this output type does not emit
+    /// entries individually.
+    fn format(self, item: &RoaPrefixRange, preserve_explicit_equal: bool) -> Option<String> {
+        let max_length = || {
+            item.max_length().or_else(|| {
+                (preserve_explicit_equal && item.has_explicit_equal_max_length())
+                    .then(|| item.prefix_length())
+            })
+        };
+        let line = match self {
+            Self::Text if preserve_explicit_equal => format!("{item:#}"),
+            Self::Text => item.to_string(),
+            Self::Tsv => format!(
+                "{}\t{}",
+                item.prefix(),
+                max_length().map(|l| l.to_string()).unwrap_or_default()
+            ),
+            #[cfg(feature = "json")]
+            Self::Jsonl => {
+                let mut entry = serde_json::Map::new();
+                _ = entry.insert("prefix".to_string(), item.prefix().to_string().into());
+                if let Some(max_length) = max_length() {
+                    _ = entry.insert("maxLength".to_string(), max_length.into());
+                }
+                serde_json::Value::Object(entry).to_string()
+            }
+            // the origin AS column of this and TSV output is prepended by
+            // the caller
+            Self::VrpCsv => format!(
+                "{},{}",
+                item.prefix(),
+                item.max_length().unwrap_or_else(|| item.prefix_length())
+            ),
+            #[cfg(feature = "json")]
+            Self::Json | Self::CountJson | Self::SummaryTable => return None,
+            #[cfg(not(feature = "json"))]
+            Self::SummaryTable => return None,
+        };
+        Some(line)
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum CanonicalFormArg {
+    /// The ordering defined by draft-ietf-sidrops-rfc6482bis
+    Draft,
+    /// Textual prefix, then numeric max_length, as used by older tooling
+    Legacy,
+}
+
+impl From<CanonicalFormArg> for CanonicalForm {
+    fn from(arg: CanonicalFormArg) -> Self {
+        match arg {
+            CanonicalFormArg::Draft => Self::Draft,
+            CanonicalFormArg::Legacy => Self::Legacy,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum AfiArg {
+    /// IPv4 entries only
+    Ipv4,
+    /// IPv6 entries only
+    Ipv6,
+}
+
+impl From<AfiArg> for Afi {
+    fn from(arg: AfiArg) -> Self {
+        match arg {
+            AfiArg::Ipv4 => Self::Ipv4,
+            AfiArg::Ipv6 => Self::Ipv6,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum DedupKey {
+    /// Entries sharing a prefix, whatever their max_length
+    Prefix,
+    /// Entries with equal prefix and max_length
+    PrefixMaxlength,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ImplicitMaxLength {
+    /// Equal to the prefix length, as in `draft-ietf-sidrops-rfc6482bis`
+    Prefix,
+    /// Equal to the maximum prefix length of the address family
+    Host,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum OutputSortKey {
+    /// Canonical order
+    Canonical,
+    /// IPv6 entries before IPv4 entries, otherwise canonical
+    Ipv6First,
+    /// Ascending prefix length, otherwise canonical
+    PrefixLength,
+}
+
+impl OutputSortKey {
+    fn sort(self, items: &mut [RoaPrefixRange]) {
+        match self {
+            Self::Canonical => {}
+            Self::Ipv6First => items.sort_by_key(|item| item.afi() == Afi::Ipv4),
+            Self::PrefixLength => items.sort_by_key(RoaPrefixRange::prefix_length),
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum IssueKind {
+    Misorder,
+    Redundant,
+    Duplicate,
+}
+
+impl IssueKind {
+    /// The process exit status when an issue of this kind is an error.
+    const fn exit_code(self) -> u8 {
+        match self {
+            Self::Misorder => 2,
+            Self::Duplicate => 3,
+            Self::Redundant => 4,
+        }
+    }
+}
+
+impl From<&Issue> for IssueKind {
+    fn from(issue: &Issue) -> Self {
+        match issue {
+            Issue::Misordered { .. } => Self::Misorder,
+            Issue::Duplicate { .. } => Self::Duplicate,
+            Issue::RedundantMaxLength { .. } => Self::Redundant,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Severity {
+    Error,
+    Warn,
+    Ignore,
+}
+
+impl Severity {
+    const fn label(self) -> &'static str {
+        match self {
+            Self::Error => "Error",
+            Self::Warn => "Warning",
+            Self::Ignore => "Ignored",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct SeverityRule {
+    kind: IssueKind,
+    severity: Severity,
+}
+
+impl FromStr for SeverityRule {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, severity) = s
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("expected KIND=SEVERITY, got '{s}'"))?;
+        Ok(Self {
+            kind: <IssueKind as ValueEnum>::from_str(kind, true).map_err(anyhow::Error::msg)?,
+            severity: <Severity as ValueEnum>::from_str(severity, true)
+                .map_err(anyhow::Error::msg)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    /// One line of prose per issue
+    Human,
+    /// A JSON array of issue objects
+    #[cfg(feature = "json")]
+    Json,
+    /// One JSON issue object per line, and nothing if there are no issues
+    #[cfg(feature = "json")]
+    Jsonl,
+    /// Test Anything Protocol
+    Tap,
+}
+
+impl ReportFormat {
+    /// Report the departures of ROA inputs from canonical wire order, always
+    /// as warnings, followed by the validation issues.
+    fn report(
+        self,
+        out: &mut dyn Write,
+        wire_order: &[(Input, RoaOrderIssue)],
+        issues: &[(Issue, Severity)],
+    ) -> anyhow::Result<()> {
+        match self {
+            Self::Human => {
+                for (input, issue) in wire_order {
+                    writeln!(
+                        out,
+                        "{}: {input}: ROA wire order was non-canonical: {issue}",
+                        Severity::Warn.label()
+                    )?;
+                }
+                for (issue, severity) in issues {
+                    writeln!(out, "{}: {issue}", severity.label())?;
+                }
+            }
+            #[cfg(feature = "json")]
+            Self::Json => {
+                let report = wire_order
+                    .iter()
+                    .map(|(input, issue)| wire_order_json(input, issue))
+                    .chain(
+                        issues
+                            .iter()
+                            .map(|(issue, severity)| issue_json(issue, *severity)),
+                    )
+                    .collect::<serde_json::Result<Vec<_>>>()?;
+                writeln!(out, "{}", serde_json::to_string(&report)?)?;
+            }
+            #[cfg(feature = "json")]
+            Self::Jsonl => {
+                for (input, issue) in wire_order {
+                    writeln!(out, "{}", wire_order_json(input, issue)?)?;
+                }
+                for (issue, severity) in issues {
+                    writeln!(out, "{}", issue_json(issue, *severity)?)?;
+                }
+            }
+            Self::Tap => {
+                if wire_order.is_empty() && issues.is_empty() {
+                    writeln!(out, "1..1")?;
+                    writeln!(out, "ok 1 - input is canonical")?;
+                } else {
+                    writeln!(out, "1..{}", wire_order.len() + issues.len())?;
+                    for (i, (input, issue)) in wire_order.iter().enumerate() {
+                        writeln!(
+                            out,
+                            "ok {} - {} in {input}: {issue} # warning",
+                            i + 1,
+                            issue.kind()
+                        )?;
+                    }
+                    let offset = wire_order.len();
+                    for (i, (issue, severity)) in issues.iter().enumerate() {
+                        let (status, directive) = match severity {
+                            Severity::Error => ("not ok", ""),
+                            Severity::Warn | Severity::Ignore => ("ok", " # warning"),
+                        };
+                        writeln!(
+                            out,
+                            "{status} {} - {} at line {}: {:#}{directive}",
+                            offset + i + 1,
+                            issue.kind(),
+                            issue.line(),
+                            issue.item()
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The JSON object for an issue, with its severity.
+#[cfg(feature = "json")]
+fn issue_json(issue: &Issue, severity: Severity) -> serde_json::Result<serde_json::Value> {
+    let mut value = serde_json::to_value(issue)?;
+    if let Some(object) = value.as_object_mut() {
+        _ = object.insert(
+            "severity".to_string(),
+            severity.label().to_lowercase().into(),
+        );
+    }
+    Ok(value)
+}
+
+/// The JSON object for a departure of a ROA input from canonical wire order,
+/// with the input it was found in, as a warning.
+#[cfg(feature = "json")]
+fn wire_order_json(input: &Input, issue: &RoaOrderIssue) -> serde_json::Result<serde_json::Value> {
+    let mut value = serde_json::to_value(issue)?;
+    if let Some(object) = value.as_object_mut() {
+        _ = object.insert("input".to_string(), input.to_string().into());
+        _ = object.insert(
+            "severity".to_string(),
+            Severity::Warn.label().to_lowercase().into(),
+        );
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_ready() -> io::Result<()> {
+        let timeout = Duration::from_millis(100);
+        // an idle terminal: the write end stays open, but nothing is written
+        let (reader, writer) = io::pipe()?;
+        assert!(!super::input_ready(move || BufReader::new(reader), timeout));
+        drop(writer);
+        let (reader, mut writer) = io::pipe()?;
+        writer.write_all(b"10.0.0.0/8\n")?;
+        assert!(super::input_ready(move || BufReader::new(reader), timeout));
+        let (reader, writer) = io::pipe()?;
+        drop(writer);
+        assert!(super::input_ready(move || BufReader::new(reader), timeout));
+        Ok(())
+    }
+}
@@ -30,8 +30,33 @@ pub(crate) struct RouteOriginAttestation {
 }
 
 impl RouteOriginAttestation {
-    pub(crate) fn ip_addr_blocks(self) -> impl Iterator<Item = RoaIpAddressFamily> {
-        self.ip_addr_blocks.into_iter()
+    pub(crate) fn new(as_id: u32, ip_addr_blocks: Vec<RoaIpAddressFamily>) -> Self {
+        Self {
+            version: Integer::from(0),
+            as_id: AsId(Integer::from(as_id)),
+            ip_addr_blocks,
+        }
+    }
+
+    /// Check that the version is 0, the only version defined.
+    pub(crate) fn check_version(&self) -> anyhow::Result<()> {
+        if self.version != Integer::from(0) {
+            anyhow::bail!(
+                "unsupported ROA version {}: only version 0 is defined",
+                self.version
+            );
+        }
+        Ok(())
+    }
+
+    pub(crate) fn as_id(&self) -> anyhow::Result<u32> {
+        self.as_id.0.to_u32().ok_or_else(|| {
+            anyhow::anyhow!("failed to convert asID value '{:?}' to u32", self.as_id.0)
+        })
+    }
+
+    pub(crate) fn ip_addr_blocks(&self) -> impl Iterator<Item = &RoaIpAddressFamily> {
+        self.ip_addr_blocks.iter()
     }
 }
 
@@ -48,6 +73,17 @@ pub(crate) struct RoaIpAddressFamily {
 }
 
 impl RoaIpAddressFamily {
+    pub(crate) fn new(afi: concrete::Afi, addresses: Vec<RoaIpAddress>) -> Self {
+        let address_family: &'static [u8] = match afi {
+            concrete::Afi::Ipv4 => &[0, 1],
+            concrete::Afi::Ipv6 => &[0, 2],
+        };
+        Self {
+            address_family: OctetString::from_static(address_family),
+            addresses,
+        }
+    }
+
     pub(crate) fn address_family(&self) -> anyhow::Result<concrete::Afi> {
         log::info!("trying to get address-family");
         match self.address_family.as_ref() {
@@ -57,8 +93,8 @@ impl RoaIpAddressFamily {
         }
     }
 
-    pub(crate) fn addresses(self) -> impl Iterator<Item = RoaIpAddress> {
-        self.addresses.into_iter()
+    pub(crate) fn addresses(&self) -> impl Iterator<Item = &RoaIpAddress> {
+        self.addresses.iter()
     }
 }
 
@@ -71,10 +107,30 @@ pub(crate) struct RoaIpAddress {
 }
 
 impl RoaIpAddress {
+    /// Construct from the network-order address octets, the prefix length,
+    /// and an explicit `max_length`, if any.
+    pub(crate) fn new(octets: &[u8], length: u8, max_length: Option<u8>) -> Self {
+        let mut address = BitString::from_slice(octets);
+        address.truncate(usize::from(length));
+        Self {
+            address,
+            max_length: max_length.map(Integer::from),
+        }
+    }
+
     pub(crate) fn address<A: Afi>(&self) -> anyhow::Result<Prefix<A>> {
         log::info!("trying to read IP prefix bits");
-        let address = Address::from_slice(self.address.as_raw_slice())
-            .context("failed to read IP address from bit string")?;
+        let raw = self.address.as_raw_slice();
+        if has_stray_bits(raw, self.address.len()) {
+            anyhow::bail!(
+                "IP address bit string {raw:02x?} has bits set beyond its length ({})",
+                self.address.len()
+            );
+        }
+        // the address of a default route has no octets at all
+        let octets = if raw.is_empty() { &[0][..] } else { raw };
+        let address =
+            Address::from_slice(octets).context("failed to read IP address from bit string")?;
         log::info!("trying to get IP prefix length");
         let length = self.address.len().try_into()?;
         Ok(Prefix::new(address, length))
@@ -93,3 +149,73 @@ impl RoaIpAddress {
             .transpose()
     }
 }
+
+/// Whether any bit of `raw` after the first `len` bits is set.
+///
+/// RFC 6482 requires the unused bits of an address to be zero, and DER
+/// requires the same of the padding bits in the final octet.
+fn has_stray_bits(raw: &[u8], len: usize) -> bool {
+    raw.iter().enumerate().any(|(i, octet)| {
+        let used = u32::try_from(len.saturating_sub(i * 8).min(8)).unwrap_or(8);
+        octet & 0xff_u8.checked_shr(used).unwrap_or(0) != 0
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ip::{Ipv4, Ipv6};
+
+    fn roa_ip_address(octets: &[u8], len: usize) -> RoaIpAddress {
+        let mut address = BitString::from_slice(octets);
+        address.truncate(len);
+        RoaIpAddress {
+            address,
+            max_length: None,
+        }
+    }
+
+    #[test]
+    fn address_without_stray_bits() -> anyhow::Result<()> {
+        let prefix = roa_ip_address(&[0b1100_0000], 2).address::<Ipv4>()?;
+        assert_eq!(prefix.to_string(), "192.0.0.0/2");
+        let prefix = roa_ip_address(&[192, 0, 2, 128], 25).address::<Ipv4>()?;
+        assert_eq!(prefix.to_string(), "192.0.2.128/25");
+        Ok(())
+    }
+
+    #[test]
+    fn address_of_default_route() -> anyhow::Result<()> {
+        let prefix = roa_ip_address(&[], 0).address::<Ipv4>()?;
+        assert_eq!(prefix.to_string(), "0.0.0.0/0");
+        let prefix = roa_ip_address(&[], 0).address::<Ipv6>()?;
+        assert_eq!(prefix.to_string(), "::/0");
+        Ok(())
+    }
+
+    #[test]
+    fn address_with_stray_bits() {
+        let err = roa_ip_address(&[0b1100_0001], 2)
+            .address::<Ipv4>()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("has bits set beyond its length (2)"));
+        let err = roa_ip_address(&[192, 0, 2, 129], 25)
+            .address::<Ipv4>()
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("has bits set beyond its length (25)"));
+    }
+
+    #[test]
+    fn stray_bits() {
+        assert!(!has_stray_bits(&[], 0));
+        assert!(!has_stray_bits(&[0xff, 0x80], 9));
+        assert!(has_stray_bits(&[0xff, 0xc0], 9));
+        assert!(has_stray_bits(&[0xff, 0x00, 0x01], 9));
+        assert!(!has_stray_bits(&[0xff; 4], 32));
+    }
+}
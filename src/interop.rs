@@ -0,0 +1,69 @@
+//! Conversions from ROA objects already decoded by the `rpki` crate.
+use ip::{Ipv4, Ipv6};
+
+use rpki::repository::roa::{Roa, RouteOriginAttestation};
+
+use crate::ir::{InnerRoaPrefixRange, RoaPrefixRange, RoaPrefixRanges};
+
+impl TryFrom<&RouteOriginAttestation> for RoaPrefixRanges {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &RouteOriginAttestation) -> Result<Self, Self::Error> {
+        log::info!("trying to convert rpki RouteOriginAttestation");
+        // the `rpki` crate resolves an absent max_length to the prefix length
+        value
+            .iter()
+            .map(|addr| match addr.address() {
+                std::net::IpAddr::V4(v4) => InnerRoaPrefixRange::<Ipv4>::from_primitives(
+                    &v4.octets(),
+                    addr.address_length(),
+                    addr.max_length(),
+                )
+                .map(RoaPrefixRange::Ipv4),
+                std::net::IpAddr::V6(v6) => InnerRoaPrefixRange::<Ipv6>::from_primitives(
+                    &v6.octets(),
+                    addr.address_length(),
+                    addr.max_length(),
+                )
+                .map(RoaPrefixRange::Ipv6),
+            })
+            .collect::<anyhow::Result<Self>>()
+            .map(|ranges| ranges.with_as_id(value.as_id().into_u32()))
+    }
+}
+
+impl TryFrom<&Roa> for RoaPrefixRanges {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Roa) -> Result<Self, Self::Error> {
+        value.content().try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    use rpki::{repository::roa::RoaBuilder, resources::Asn};
+
+    use super::*;
+
+    #[test]
+    fn from_rpki_attestation() -> anyhow::Result<()> {
+        let mut builder = RoaBuilder::new(Asn::from_u32(65000));
+        builder.push_v6_addr(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 0), 32, Some(48));
+        builder.push_v4_addr(Ipv4Addr::new(192, 0, 2, 0), 24, None);
+        builder.push_v4_addr(Ipv4Addr::new(10, 0, 0, 0), 8, Some(8));
+        let ranges: RoaPrefixRanges = (&builder.to_attestation()).try_into()?;
+        assert_eq!(ranges.as_id(), Some(65000));
+        let output: Vec<_> = ranges
+            .into_iter()
+            .map(|(item, _)| item.to_string())
+            .collect();
+        assert_eq!(
+            output,
+            vec!["10.0.0.0/8", "192.0.2.0/24", "2001:db8::/32-48"]
+        );
+        Ok(())
+    }
+}
@@ -1,18 +1,29 @@
-use std::{cmp::Ordering, collections::BTreeMap, fmt, str::FromStr};
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    str::FromStr,
+};
 
 use anyhow::Context;
 
+use chrono::{DateTime, FixedOffset};
+
 use ip::{
     any,
-    concrete::{self, Prefix, PrefixLength},
+    concrete::{self, Address, Prefix, PrefixLength},
     Afi, Ipv4, Ipv6,
 };
 
-use rasn::der;
+#[cfg(feature = "serde")]
+use serde::{de, ser::SerializeStruct, Deserialize, Deserializer, Serialize, Serializer};
 
-use rasn_cms::{SignedData, CONTENT_SIGNED_DATA};
-
-use crate::econtent::{RoaContentInfo, RouteOriginAttestation, ID_CT_ROUTE_ORIGIN_AUTHZ};
+use crate::{
+    econtent::{RoaIpAddress, RoaIpAddressFamily, RouteOriginAttestation},
+    roa::{unarmor, Roa},
+};
 
 #[derive(Debug, Copy, Clone)]
 enum MaxLength<A: Afi> {
@@ -49,14 +60,20 @@ impl<A: Afi> Ord for MaxLength<A> {
     }
 }
 
+/// A ROA prefix range in a single address family.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub(crate) struct InnerRoaPrefixRange<A: Afi> {
+pub struct InnerRoaPrefixRange<A: Afi> {
     prefix: Prefix<A>,
     max_length: MaxLength<A>,
 }
 
 impl<A: Afi> InnerRoaPrefixRange<A> {
-    fn new(prefix: Prefix<A>, max_length: Option<PrefixLength<A>>) -> anyhow::Result<Self> {
+    /// Construct from a prefix and an optional `max_length`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_length` is less than the prefix length.
+    pub fn new(prefix: Prefix<A>, max_length: Option<PrefixLength<A>>) -> anyhow::Result<Self> {
         if let Some(max_length) = max_length {
             match max_length.cmp(&prefix.length()) {
                 Ordering::Less => {
@@ -80,6 +97,51 @@ impl<A: Afi> InnerRoaPrefixRange<A> {
             })
         }
     }
+
+    /// Construct from the network-order address octets and primitive lengths.
+    ///
+    /// A `max_length` equal to the prefix length is taken to be implicit.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the octets or lengths are not valid for the
+    /// address family.
+    pub fn from_primitives(octets: &[u8], length: u8, max_length: u8) -> anyhow::Result<Self> {
+        let address = Address::from_slice(octets).context("failed to read IP address")?;
+        let length =
+            PrefixLength::from_primitive(length).context("failed to construct prefix-length")?;
+        let max_length =
+            PrefixLength::from_primitive(max_length).context("failed to construct max_length")?;
+        Self::new(
+            Prefix::new(address, length),
+            (max_length > length).then_some(max_length),
+        )
+    }
+
+    /// The prefix of the range.
+    #[must_use]
+    pub const fn prefix(&self) -> Prefix<A> {
+        self.prefix
+    }
+
+    /// The `max_length` of the range, resolving an implicit value to the
+    /// prefix length.
+    #[must_use]
+    pub fn max_length(&self) -> PrefixLength<A> {
+        match self.max_length {
+            MaxLength::Explicit(max_length) => max_length,
+            MaxLength::ImplicitEqual | MaxLength::ExplicitEqual => self.prefix.length(),
+        }
+    }
+
+    fn effective_max_length(&self) -> u8 {
+        match self.max_length {
+            MaxLength::Explicit(max_length) => max_length.into_primitive(),
+            MaxLength::ImplicitEqual | MaxLength::ExplicitEqual => {
+                self.prefix.length().into_primitive()
+            }
+        }
+    }
 }
 
 impl<A: Afi> PartialOrd for InnerRoaPrefixRange<A> {
@@ -100,29 +162,199 @@ impl<A: Afi> Ord for InnerRoaPrefixRange<A> {
     }
 }
 
+/// Formats the range in its canonical text form.
+///
+/// The alternate flag (`{:#}`) preserves an explicitly specified `max_length`
+/// that is equal to the prefix length, rather than collapsing it.
 impl<A: Afi> fmt::Display for InnerRoaPrefixRange<A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        if let MaxLength::Explicit(max_length) = self.max_length {
-            write!(f, "{}-{}", self.prefix, max_length)
-        } else {
-            self.prefix.fmt(f)
+        match self.max_length {
+            MaxLength::Explicit(max_length) => write!(f, "{}-{}", self.prefix, max_length),
+            MaxLength::ExplicitEqual if f.alternate() => {
+                write!(f, "{}-{}", self.prefix, self.prefix.length())
+            }
+            MaxLength::ImplicitEqual | MaxLength::ExplicitEqual => self.prefix.fmt(f),
         }
     }
 }
 
+/// A ROA prefix range: an IP prefix together with an optional `max_length`.
+///
+/// Items are ordered according to the canonicalization rules of
+/// `draft-ietf-sidrops-rfc6482bis`, and parsed from and formatted as text of
+/// the form `prefix/length[-max_length]`.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub(crate) enum RoaPrefixRange {
+pub enum RoaPrefixRange {
+    /// An IPv4 prefix range.
     Ipv4(InnerRoaPrefixRange<Ipv4>),
+    /// An IPv6 prefix range.
     Ipv6(InnerRoaPrefixRange<Ipv6>),
 }
 
 impl RoaPrefixRange {
-    pub(crate) const fn has_explicit_equal_max_length(&self) -> bool {
+    /// Whether the `max_length` was explicitly specified, but equal to the
+    /// prefix length.
+    #[must_use]
+    pub const fn has_explicit_equal_max_length(&self) -> bool {
         match self {
             Self::Ipv4(inner) => matches!(inner.max_length, MaxLength::ExplicitEqual),
             Self::Ipv6(inner) => matches!(inner.max_length, MaxLength::ExplicitEqual),
         }
     }
+
+    /// The same item, with an implicit `max_length` taken to be the maximum
+    /// prefix length of the address family, rather than the prefix length.
+    fn with_host_max_length(self) -> Self {
+        fn inner<A: Afi>(inner: InnerRoaPrefixRange<A>, host: u8) -> InnerRoaPrefixRange<A> {
+            match (inner.max_length, PrefixLength::from_primitive(host)) {
+                (MaxLength::ImplicitEqual, Ok(host)) if host > inner.prefix.length() => {
+                    InnerRoaPrefixRange {
+                        prefix: inner.prefix,
+                        max_length: MaxLength::Explicit(host),
+                    }
+                }
+                _ => inner,
+            }
+        }
+        match self {
+            Self::Ipv4(item) => Self::Ipv4(inner(item, 32)),
+            Self::Ipv6(item) => Self::Ipv6(inner(item, 128)),
+        }
+    }
+
+    /// The same prefix, with an implicit `max_length`.
+    const fn without_max_length(self) -> Self {
+        match self {
+            Self::Ipv4(inner) => Self::Ipv4(InnerRoaPrefixRange {
+                prefix: inner.prefix,
+                max_length: MaxLength::ImplicitEqual,
+            }),
+            Self::Ipv6(inner) => Self::Ipv6(InnerRoaPrefixRange {
+                prefix: inner.prefix,
+                max_length: MaxLength::ImplicitEqual,
+            }),
+        }
+    }
+
+    /// The same range, with an explicit `max_length` equal to the prefix
+    /// length collapsed into the implicit form.
+    const fn collapsed(self) -> Self {
+        match self {
+            Self::Ipv4(InnerRoaPrefixRange {
+                prefix,
+                max_length: MaxLength::ExplicitEqual,
+            }) => Self::Ipv4(InnerRoaPrefixRange {
+                prefix,
+                max_length: MaxLength::ImplicitEqual,
+            }),
+            Self::Ipv6(InnerRoaPrefixRange {
+                prefix,
+                max_length: MaxLength::ExplicitEqual,
+            }) => Self::Ipv6(InnerRoaPrefixRange {
+                prefix,
+                max_length: MaxLength::ImplicitEqual,
+            }),
+            _ => self,
+        }
+    }
+
+    /// The prefix of the item.
+    #[must_use]
+    pub const fn prefix(&self) -> any::Prefix {
+        match self {
+            Self::Ipv4(inner) => any::Prefix::Ipv4(inner.prefix),
+            Self::Ipv6(inner) => any::Prefix::Ipv6(inner.prefix),
+        }
+    }
+
+    /// The address family of the item.
+    #[must_use]
+    pub const fn afi(&self) -> concrete::Afi {
+        match self {
+            Self::Ipv4(_) => concrete::Afi::Ipv4,
+            Self::Ipv6(_) => concrete::Afi::Ipv6,
+        }
+    }
+
+    /// The length of the prefix.
+    #[must_use]
+    pub fn prefix_length(&self) -> u8 {
+        match self {
+            Self::Ipv4(inner) => inner.prefix.length().into_primitive(),
+            Self::Ipv6(inner) => inner.prefix.length().into_primitive(),
+        }
+    }
+
+    /// The `max_length`, if it is explicitly specified and greater than the
+    /// prefix length.
+    #[must_use]
+    pub fn max_length(&self) -> Option<u8> {
+        match self {
+            Self::Ipv4(InnerRoaPrefixRange {
+                max_length: MaxLength::Explicit(max_length),
+                ..
+            }) => Some(max_length.into_primitive()),
+            Self::Ipv6(InnerRoaPrefixRange {
+                max_length: MaxLength::Explicit(max_length),
+                ..
+            }) => Some(max_length.into_primitive()),
+            _ => None,
+        }
+    }
+
+    /// The number of more-specific prefix lengths authorized, beyond the
+    /// prefix length itself.
+    #[must_use]
+    pub fn more_specific_levels(&self) -> u8 {
+        self.max_length()
+            .map_or(0, |max_length| max_length - self.prefix_length())
+    }
+
+    /// Whether the `max_length` exceeds the prefix length by more than
+    /// `limit` bits, authorizing more levels of more-specific prefixes than
+    /// intended.
+    #[must_use]
+    pub fn exceeds_expansion(&self, limit: u8) -> bool {
+        self.more_specific_levels() > limit
+    }
+
+    /// Whether the `max_length` is explicitly extended all the way to the
+    /// maximum for the address family, authorizing every more-specific
+    /// prefix down to host routes.
+    #[must_use]
+    pub fn has_open_max_length(&self) -> bool {
+        let afi_max = match self.afi() {
+            concrete::Afi::Ipv4 => 32,
+            concrete::Afi::Ipv6 => 128,
+        };
+        self.max_length() == Some(afi_max)
+    }
+
+    /// Whether every route authorized by `other` is also authorized by this
+    /// item.
+    #[must_use]
+    pub fn covers(&self, other: &Self) -> bool {
+        RawRange::from(self).covers(&other.into())
+    }
+
+    /// Heuristically detect an IPv6 prefix that embeds an IPv4 address,
+    /// either IPv4-mapped (`::ffff:0:0/96`) or IPv4-compatible (`::/96`),
+    /// which is likely an IPv4 prefix written in the wrong address family.
+    ///
+    /// The unspecified and loopback addresses, `::` and `::1`, are not
+    /// taken to be IPv4-compatible, and an IPv4 prefix is never flagged.
+    #[must_use]
+    pub fn is_ipv4_embedded(&self) -> bool {
+        match self {
+            Self::Ipv4(_) => false,
+            Self::Ipv6(inner) => {
+                let address = inner.prefix.prefix().into_primitive();
+                let high = address >> 32;
+                inner.prefix.length().into_primitive() >= 96
+                    && (high == 0xffff || (high == 0 && address > 1))
+            }
+        }
+    }
 }
 
 impl Ord for RoaPrefixRange {
@@ -142,38 +374,136 @@ impl PartialOrd for RoaPrefixRange {
     }
 }
 
-impl FromStr for RoaPrefixRange {
-    type Err = anyhow::Error;
+#[derive(Debug, Clone, Copy)]
+enum RawMaxLength<'a> {
+    /// An absolute `max_length`, written as `-N`.
+    Absolute(&'a str),
+    /// A `max_length` relative to the prefix length, written as `+N`.
+    Delta(&'a str),
+}
 
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
+impl<A: Afi> InnerRoaPrefixRange<A> {
+    fn from_raw(prefix: Prefix<A>, raw_len: Option<RawMaxLength<'_>>) -> anyhow::Result<Self> {
+        let max_length = raw_len
+            .map(|raw| match raw {
+                RawMaxLength::Absolute(l) => {
+                    PrefixLength::<A>::from_primitive(l.parse()?).context("failed to parse max_length")
+                }
+                RawMaxLength::Delta(d) => {
+                    let delta: u8 = d.parse().context("failed to parse max_length delta")?;
+                    if delta == 0 {
+                        anyhow::bail!("got redundant max_length delta '+0' ({prefix})");
+                    }
+                    prefix
+                        .length()
+                        .into_primitive()
+                        .checked_add(delta)
+                        .and_then(|l| PrefixLength::<A>::from_primitive(l).ok())
+                        .ok_or_else(|| {
+                            anyhow::anyhow!(
+                                "max_length delta '+{delta}' exceeds the maximum prefix length ({prefix})"
+                            )
+                        })
+                }
+            })
+            .transpose()?;
+        Self::new(prefix, max_length)
+    }
+}
+
+/// How a prefix written with bits set beyond its length, such as
+/// `10.0.0.5/8`, is handled when it is parsed.
+///
+/// The parsing functions without a `host_bits` parameter reject it.
+#[derive(Debug, Clone, Copy)]
+pub enum HostBits {
+    /// Reject the prefix, since it is ambiguous which prefix was intended.
+    Reject,
+    /// Mask the address to the prefix length, calling the function with the
+    /// prefix as written and as masked, for example to warn about it.
+    Mask(fn(&str, &str)),
+}
+
+impl RoaPrefixRange {
+    /// Parse an item from its text form, as by its [`FromStr`]
+    /// implementation, handling a prefix with host bits set as given by
+    /// `host_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `input` is not a valid item.
+    pub fn from_str_with(input: &str, host_bits: HostBits) -> anyhow::Result<Self> {
         let (raw_prefix, raw_len) = if let Some((p, l)) = input.split_once('-') {
-            (p, Some(l))
+            (p, Some(RawMaxLength::Absolute(l)))
+        } else if let Some((p, d)) = input.split_once('+') {
+            (p, Some(RawMaxLength::Delta(d)))
         } else {
             (input, None)
         };
-        match raw_prefix.parse::<any::Prefix>()? {
+        // leniently accept an address in square brackets, as copied from a URL
+        let raw_prefix = match raw_prefix.split_once('/') {
+            Some((address, length)) if address.starts_with('[') && address.ends_with(']') => {
+                Cow::Owned(format!("{}/{length}", &address[1..address.len() - 1]))
+            }
+            _ => Cow::Borrowed(raw_prefix),
+        };
+        match parse_prefix(&raw_prefix, host_bits)? {
             any::Prefix::Ipv4(prefix) => {
-                let max_length = raw_len
-                    .map(|l| {
-                        PrefixLength::<Ipv4>::from_primitive(l.parse()?)
-                            .context("failed to parse max_length")
-                    })
-                    .transpose()?;
-                InnerRoaPrefixRange::new(prefix, max_length).map(Self::Ipv4)
+                InnerRoaPrefixRange::from_raw(prefix, raw_len).map(Self::Ipv4)
             }
             any::Prefix::Ipv6(prefix) => {
-                let max_length = raw_len
-                    .map(|l| {
-                        PrefixLength::<Ipv6>::from_primitive(l.parse()?)
-                            .context("failed to parse max_length")
-                    })
-                    .transpose()?;
-                InnerRoaPrefixRange::new(prefix, max_length).map(Self::Ipv6)
+                InnerRoaPrefixRange::from_raw(prefix, raw_len).map(Self::Ipv6)
             }
         }
     }
 }
 
+impl FromStr for RoaPrefixRange {
+    type Err = anyhow::Error;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        Self::from_str_with(input, HostBits::Reject)
+    }
+}
+
+/// Parse a prefix in CIDR form, handling an address with bits set beyond the
+/// prefix length as given by `host_bits`.
+fn parse_prefix(input: &str, host_bits: HostBits) -> anyhow::Result<any::Prefix> {
+    match (without_host_bits(input), host_bits) {
+        (None, _) => Ok(input.parse()?),
+        (Some(network), HostBits::Mask(report)) => {
+            report(input, &network);
+            Ok(network.parse()?)
+        }
+        (Some(network), HostBits::Reject) => anyhow::bail!(
+            "prefix {input} has host bits set beyond its length (did you mean {network}?)"
+        ),
+    }
+}
+
+/// The prefix written as `input` with its address masked to the prefix
+/// length, if the address has any bits set beyond it.
+fn without_host_bits(input: &str) -> Option<String> {
+    let (address, length) = input.split_once('/')?;
+    let length: u32 = length.parse().ok()?;
+    let address: IpAddr = address.parse().ok()?;
+    let masked = match address {
+        IpAddr::V4(v4) => IpAddr::V4(Ipv4Addr::from(
+            u32::from(v4)
+                & u32::MAX
+                    .checked_shl(32_u32.checked_sub(length)?)
+                    .unwrap_or(0),
+        )),
+        IpAddr::V6(v6) => IpAddr::V6(Ipv6Addr::from(
+            u128::from(v6)
+                & u128::MAX
+                    .checked_shl(128_u32.checked_sub(length)?)
+                    .unwrap_or(0),
+        )),
+    };
+    (masked != address).then(|| format!("{masked}/{length}"))
+}
+
 impl fmt::Display for RoaPrefixRange {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -183,137 +513,4033 @@ impl fmt::Display for RoaPrefixRange {
     }
 }
 
-pub(crate) struct RoaPrefixRanges(BTreeMap<RoaPrefixRange, usize>);
-
-impl RoaPrefixRanges {
-    pub(crate) fn from_text<S, I, E>(iter: I) -> anyhow::Result<Self>
+/// Serializes as an object with the prefix in CIDR form, and a `maxLength`
+/// only if it is explicitly specified and greater than the prefix length.
+#[cfg(feature = "serde")]
+impl Serialize for RoaPrefixRange {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: AsRef<str>,
-        I: IntoIterator<Item = Result<S, E>>,
-        E: std::error::Error + Send + Sync + 'static,
+        S: Serializer,
     {
-        iter.into_iter()
-            .map(|line| line.context("failed to get input line")?.as_ref().parse())
-            .collect()
+        let max_length = self.max_length();
+        let len = if max_length.is_some() { 2 } else { 1 };
+        let mut state = serializer.serialize_struct("RoaPrefixRange", len)?;
+        state.serialize_field("prefix", &self.prefix().to_string())?;
+        if let Some(max_length) = max_length {
+            state.serialize_field("maxLength", &max_length)?;
+        }
+        state.end()
     }
+}
 
-    pub(crate) fn from_roa(bytes: &[u8]) -> anyhow::Result<Self> {
-        log::info!("trying to decode ROA from input bytes");
-        der::decode::<RoaContentInfo>(bytes)
-            .context("failed to decode ContentInfo")?
-            .try_into()
+/// The JSON object form of a range, as produced by [`RoaPrefixRange`]'s
+/// `Serialize` implementation. Unknown fields are ignored.
+#[cfg(feature = "serde")]
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct JsonRange {
+    prefix: String,
+    max_length: Option<u8>,
+}
+
+#[cfg(feature = "serde")]
+impl JsonRange {
+    /// Convert to an item, handling a prefix with host bits set as given by
+    /// `host_bits`.
+    fn into_range(self, host_bits: HostBits) -> anyhow::Result<RoaPrefixRange> {
+        fn inner<A: Afi>(
+            prefix: Prefix<A>,
+            max_length: Option<u8>,
+        ) -> anyhow::Result<InnerRoaPrefixRange<A>> {
+            let max_length = max_length
+                .map(PrefixLength::from_primitive)
+                .transpose()
+                .context("failed to construct max_length")?;
+            InnerRoaPrefixRange::new(prefix, max_length)
+        }
+        match parse_prefix(&self.prefix, host_bits)? {
+            any::Prefix::Ipv4(prefix) => inner(prefix, self.max_length).map(RoaPrefixRange::Ipv4),
+            any::Prefix::Ipv6(prefix) => inner(prefix, self.max_length).map(RoaPrefixRange::Ipv6),
+        }
     }
 }
 
-impl FromIterator<RoaPrefixRange> for RoaPrefixRanges {
-    fn from_iter<I>(iter: I) -> Self
+#[cfg(feature = "serde")]
+impl TryFrom<JsonRange> for RoaPrefixRange {
+    type Error = anyhow::Error;
+
+    fn try_from(value: JsonRange) -> Result<Self, Self::Error> {
+        value.into_range(HostBits::Reject)
+    }
+}
+
+/// Deserializes from an object with a `prefix` in CIDR form and an optional
+/// `maxLength`.
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RoaPrefixRange {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
-        I: IntoIterator<Item = RoaPrefixRange>,
+        D: Deserializer<'de>,
     {
-        let inner = iter
-            .into_iter()
-            .enumerate()
-            .map(|(i, item)| (item, i))
-            .collect();
-        Self(inner)
+        JsonRange::deserialize(deserializer)?
+            .try_into()
+            .map_err(|err: anyhow::Error| de::Error::custom(format!("{err:#}")))
     }
 }
 
-impl IntoIterator for RoaPrefixRanges {
-    type Item = (RoaPrefixRange, usize);
-    type IntoIter = <BTreeMap<RoaPrefixRange, usize> as IntoIterator>::IntoIter;
+/// (De)serialize a [`RoaPrefixRange`] in its text form, such as
+/// `"10.0.0.0/8"` or `"10.0.0.0/8-24"`, rather than as an object.
+///
+/// For use with `#[serde(with = "roasort::as_text")]`:
+///
+/// ```
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Config {
+///     #[serde(with = "roasort::as_text")]
+///     range: roasort::RoaPrefixRange,
+/// }
+///
+/// let config: Config = serde_json::from_str(r#"{"range": "10.0.0.0/8-24"}"#)?;
+/// assert_eq!(serde_json::to_string(&config)?, r#"{"range":"10.0.0.0/8-24"}"#);
+/// # Ok::<_, serde_json::Error>(())
+/// ```
+#[cfg(feature = "serde")]
+pub mod as_text {
+    use super::{de, Deserialize, Deserializer, RoaPrefixRange, Serializer};
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.0.into_iter()
+    /// Serialize `range` as a string, keeping an explicit `max_length`
+    /// equal to the prefix length.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from the serializer.
+    pub fn serialize<S>(range: &RoaPrefixRange, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(&format_args!("{range:#}"))
+    }
+
+    /// Deserialize a range from a string, using its `FromStr` implementation.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not a string or fails to parse.
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<RoaPrefixRange, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(|err: anyhow::Error| de::Error::custom(format!("{err:#}")))
     }
 }
 
-impl TryFrom<RoaContentInfo> for RoaPrefixRanges {
-    type Error = anyhow::Error;
+/// A range in a family-independent numeric form, for prefix arithmetic.
+///
+/// IPv4 addresses occupy the low 32 bits of `address`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct RawRange {
+    ipv6: bool,
+    address: u128,
+    length: u8,
+    max_length: u8,
+}
 
-    fn try_from(value: RoaContentInfo) -> Result<Self, Self::Error> {
-        log::info!("checking for id-ct-SignedData content-type");
-        if CONTENT_SIGNED_DATA != value.content_type {
-            let msg = "invalid OID for SignedData content";
-            log::error!("{msg}");
-            anyhow::bail!(msg);
-        }
-        log::info!("trying to decode content as SignedData");
-        let content = value.content.as_bytes();
-        let signed_data: SignedData =
-            der::decode(content).context("failed to decode CMS content")?;
-
-        let encap_content_info = signed_data.encap_content_info;
-        if ID_CT_ROUTE_ORIGIN_AUTHZ != encap_content_info.content_type {
-            anyhow::bail!("invalid OID for ROA eContent");
-        }
-        log::info!("trying to decode econtent as RouteOriginAttestation");
-        let roa_econtent: RouteOriginAttestation = encap_content_info
-            .content
-            .ok_or_else(|| anyhow::anyhow!("failed to extract eContent bytes"))
-            .and_then(|bytes| der::decode(bytes.as_ref()).context("failed to decode eContent"))?;
-
-        roa_econtent
-            .ip_addr_blocks()
-            .flat_map(|roa_ip_addr_family| {
-                let afi = roa_ip_addr_family.address_family();
-                roa_ip_addr_family
-                    .addresses()
-                    .map(move |roa_ip_addr| match &afi {
-                        Ok(concrete::Afi::Ipv4) => {
-                            Ok(RoaPrefixRange::Ipv4(InnerRoaPrefixRange::new(
-                                roa_ip_addr.address()?,
-                                roa_ip_addr.max_length::<Ipv4>()?,
-                            )?))
-                        }
-                        Ok(concrete::Afi::Ipv6) => {
-                            Ok(RoaPrefixRange::Ipv6(InnerRoaPrefixRange::new(
-                                roa_ip_addr.address()?,
-                                roa_ip_addr.max_length::<Ipv6>()?,
-                            )?))
-                        }
-                        Err(_) => anyhow::bail!("invalid IP address family indicator"),
-                    })
-            })
-            .collect::<Result<_, _>>()
+impl RawRange {
+    const fn width(&self) -> u8 {
+        if self.ipv6 {
+            128
+        } else {
+            32
+        }
+    }
+
+    fn mask(&self, length: u8) -> u128 {
+        let all = if self.ipv6 {
+            u128::MAX
+        } else {
+            u128::from(u32::MAX)
+        };
+        all.checked_shl(u32::from(self.width() - length))
+            .map_or(0, |mask| mask & all)
+    }
+
+    fn sibling(&self) -> Option<Self> {
+        (self.length > 0).then(|| Self {
+            address: self.address ^ (1 << (self.width() - self.length)),
+            ..*self
+        })
+    }
+
+    fn children(&self) -> Option<[Self; 2]> {
+        (self.length < self.width()).then(|| {
+            let lower = Self {
+                length: self.length + 1,
+                ..*self
+            };
+            [
+                lower,
+                Self {
+                    address: self.address | (1 << (self.width() - self.length - 1)),
+                    ..lower
+                },
+            ]
+        })
+    }
+
+    fn parent(&self) -> Option<Self> {
+        self.length.checked_sub(1).map(|length| Self {
+            address: self.address & self.mask(length),
+            length,
+            ..*self
+        })
+    }
+
+    /// Whether everything authorized by `other` is also authorized by this.
+    fn covers(&self, other: &Self) -> bool {
+        self.ipv6 == other.ipv6
+            && self.length <= other.length
+            && other.max_length <= self.max_length
+            && other.address & self.mask(self.length) == self.address
+    }
+
+    /// Whether some other range in `ranges` covers this one.
+    ///
+    /// Only the ranges with a prefix containing this one need be looked up:
+    /// one for each prefix length up to its own.
+    fn is_covered_in<V>(&self, ranges: &BTreeMap<Self, V>) -> bool {
+        (0..=self.length).any(|length| {
+            let lower = Self {
+                address: self.address & self.mask(length),
+                length,
+                ..*self
+            };
+            let upper = Self {
+                max_length: u8::MAX,
+                ..lower
+            };
+            ranges.range(lower..=upper).any(|(other, _)| other != self)
+        })
+    }
+
+    fn to_range(self) -> anyhow::Result<RoaPrefixRange> {
+        if self.ipv6 {
+            InnerRoaPrefixRange::from_primitives(
+                &self.address.to_be_bytes(),
+                self.length,
+                self.max_length,
+            )
+            .map(RoaPrefixRange::Ipv6)
+        } else {
+            InnerRoaPrefixRange::from_primitives(
+                &u32::try_from(self.address)?.to_be_bytes(),
+                self.length,
+                self.max_length,
+            )
+            .map(RoaPrefixRange::Ipv4)
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl From<&RoaPrefixRange> for RawRange {
+    fn from(item: &RoaPrefixRange) -> Self {
+        match item {
+            RoaPrefixRange::Ipv4(inner) => Self {
+                ipv6: false,
+                address: u128::from(inner.prefix.prefix().into_primitive()),
+                length: inner.prefix.length().into_primitive(),
+                max_length: inner.effective_max_length(),
+            },
+            RoaPrefixRange::Ipv6(inner) => Self {
+                ipv6: true,
+                address: inner.prefix.prefix().into_primitive(),
+                length: inner.prefix.length().into_primitive(),
+                max_length: inner.effective_max_length(),
+            },
+        }
+    }
+}
 
-    #[test]
-    fn read_from_text() -> anyhow::Result<()> {
-        let input = vec![
-            Ok::<_, std::io::Error>("10.0.0.0/24"),
-            Ok("10.0.0.0/24-24"),
-            Ok("10.0.0.0/8"),
-            Ok("2001:db8:db8::/48"),
-            Ok("2001:db8::/32"),
-        ];
-        let expect = vec![
-            "10.0.0.0/8",
-            "10.0.0.0/24",
-            "2001:db8::/32",
-            "2001:db8:db8::/48",
-        ];
-        let mut errs = 0usize;
-        let output: Vec<_> = RoaPrefixRanges::from_text(input)?
-            .into_iter()
-            .enumerate()
-            .map(|(i, (item, j))| {
-                if i != j {
-                    errs += 1;
-                };
-                if item.has_explicit_equal_max_length() {
-                    errs += 1;
+const BINARY_INPUT_HINT: &str = "input looks like binary; did you mean `-t roa`?";
+
+/// A line of text input, with its one-based line number.
+#[derive(Debug)]
+struct TextLine<S> {
+    number: usize,
+    text: S,
+}
+
+impl<S: AsRef<str>> TextLine<S> {
+    /// The text of the line, without any comment or surrounding whitespace.
+    fn content(&self) -> &str {
+        strip_comment(self.text.as_ref()).trim()
+    }
+
+    /// Give an error the context of the line number and text.
+    fn locate<T>(&self, result: anyhow::Result<T>) -> anyhow::Result<T> {
+        result.with_context(|| format!("line {}: '{}'", self.number, self.text.as_ref()))
+    }
+}
+
+/// Read lines of text input, skipping blank lines and comments.
+fn text_lines<S, I, E>(iter: I) -> impl Iterator<Item = anyhow::Result<TextLine<S>>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    iter.into_iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let line = line.map_err(|err| {
+                let binary = (&err as &(dyn std::error::Error + 'static))
+                    .downcast_ref::<std::io::Error>()
+                    .is_some_and(|err| err.kind() == std::io::ErrorKind::InvalidData);
+                let err = anyhow::Error::new(err).context("failed to get input line");
+                if binary {
+                    err.context(BINARY_INPUT_HINT)
+                } else {
+                    err
                 }
-                item.to_string()
+            })?;
+            if line
+                .as_ref()
+                .chars()
+                .any(|c| c.is_control() && c != '\t' && c != '\r')
+            {
+                anyhow::bail!(BINARY_INPUT_HINT);
+            }
+            Ok(TextLine {
+                number: i + 1,
+                text: line,
             })
-            .collect();
-        assert_eq!(output, expect);
-        assert_eq!(errs, 3);
+        })
+        .filter(|line| !matches!(line, Ok(line) if line.content().is_empty()))
+}
+
+/// Parse lines of text input into items, in input order.
+///
+/// Leading and trailing whitespace is ignored. Blank lines and `#` comments,
+/// either on a line of their own or trailing an item, are skipped.
+///
+/// An error for a line that cannot be parsed is given the context of its
+/// one-based line number and text.
+pub fn parse_text<S, I, E>(iter: I) -> impl Iterator<Item = anyhow::Result<RoaPrefixRange>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse_text_with(iter, HostBits::Reject)
+}
+
+/// Parse lines of text input into items, in input order, as by
+/// [`parse_text`], handling prefixes with host bits set as given by
+/// `host_bits`.
+pub fn parse_text_with<S, I, E>(
+    iter: I,
+    host_bits: HostBits,
+) -> impl Iterator<Item = anyhow::Result<RoaPrefixRange>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse_numbered_text_with(iter, host_bits).map(|item| item.map(|(_, item)| item))
+}
+
+/// Parse lines of text input into items, in input order, each with the
+/// one-based number of the line it was read from.
+///
+/// Lines are handled as by [`parse_text`]; the line numbers count the blank
+/// lines and comments that are skipped.
+pub fn parse_numbered_text<S, I, E>(
+    iter: I,
+) -> impl Iterator<Item = anyhow::Result<(usize, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse_numbered_text_with(iter, HostBits::Reject)
+}
+
+/// Parse lines of text input into items, each with its line number, as by
+/// [`parse_numbered_text`], handling prefixes with host bits set as given
+/// by `host_bits`.
+pub fn parse_numbered_text_with<S, I, E>(
+    iter: I,
+    host_bits: HostBits,
+) -> impl Iterator<Item = anyhow::Result<(usize, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    text_lines(iter).map(move |line| {
+        let line = line?;
+        line.locate(RoaPrefixRange::from_str_with(line.content(), host_bits))
+            .map(|item| (line.number, item))
+    })
+}
+
+/// Parse lines of text input into items, each with its origin AS number, in
+/// input order.
+///
+/// An item is preceded by its origin AS, written as `AS<n>` and separated
+/// from the item by `:` or whitespace. Alternatively, a line holding only
+/// `AS<n>`, optionally followed by `:`, gives the origin AS of the items
+/// that follow it without one:
+///
+/// ```text
+/// AS65000 192.0.2.0/24
+/// AS65001:
+/// 198.51.100.0/24
+/// 2001:db8::/32-48
+/// ```
+///
+/// Whitespace, blank lines and comments are handled as by [`parse_text`].
+pub fn parse_asn_text<S, I, E>(
+    iter: I,
+) -> impl Iterator<Item = anyhow::Result<(u32, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse_asn_text_with(iter, HostBits::Reject)
+}
+
+/// Parse lines of text input into items, each with its origin AS number, as
+/// by [`parse_asn_text`], handling prefixes with host bits set as given by
+/// `host_bits`.
+pub fn parse_asn_text_with<S, I, E>(
+    iter: I,
+    host_bits: HostBits,
+) -> impl Iterator<Item = anyhow::Result<(u32, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut current = None;
+    text_lines(iter).filter_map(move |line| {
+        line.and_then(|line| {
+            let (asn, entry) = line.locate(split_asn(line.content()))?;
+            if entry.is_empty() {
+                current = asn;
+                return Ok(None);
+            }
+            let asn = line.locate(asn.or(current).context("no origin AS given for item"))?;
+            line.locate(RoaPrefixRange::from_str_with(entry, host_bits))
+                .map(|item| Some((asn, item)))
+        })
+        .transpose()
+    })
+}
+
+/// Split a leading `AS<n>` origin AS number, followed by `:` or whitespace,
+/// from the content of a line.
+fn split_asn(content: &str) -> anyhow::Result<(Option<u32>, &str)> {
+    let rest = match content.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("AS") => &content[2..],
+        _ => return Ok((None, content)),
+    };
+    let end = rest
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(rest.len());
+    let (digits, rest) = rest.split_at(end);
+    let asn = digits.parse().context("failed to parse origin AS number")?;
+    let entry = if let Some(entry) = rest.strip_prefix(':') {
+        entry
+    } else if rest.is_empty() || rest.starts_with(char::is_whitespace) {
+        rest
+    } else {
+        anyhow::bail!("expected ':' or whitespace after AS{asn}");
+    };
+    Ok((Some(asn), entry.trim_start()))
+}
+
+/// The prefix of a `route:` or `route6:` attribute, or `None` for any other
+/// line of RPSL.
+fn rpsl_route<S: AsRef<str>>(
+    line: &TextLine<S>,
+    host_bits: HostBits,
+) -> anyhow::Result<Option<RoaPrefixRange>> {
+    // continuation lines, and `%` comments as written by whois servers
+    if line.text.as_ref().starts_with([' ', '\t', '+', '%']) {
+        return Ok(None);
+    }
+    let Some((name, value)) = line.content().split_once(':') else {
+        return Ok(None);
+    };
+    let ipv6 = if name.eq_ignore_ascii_case("route") {
+        false
+    } else if name.eq_ignore_ascii_case("route6") {
+        true
+    } else {
+        return Ok(None);
+    };
+    let prefix = parse_prefix(value.trim(), host_bits).context("failed to parse route prefix")?;
+    match (ipv6, prefix) {
+        (false, any::Prefix::Ipv4(prefix)) => {
+            InnerRoaPrefixRange::new(prefix, None).map(|inner| Some(RoaPrefixRange::Ipv4(inner)))
+        }
+        (true, any::Prefix::Ipv6(prefix)) => {
+            InnerRoaPrefixRange::new(prefix, None).map(|inner| Some(RoaPrefixRange::Ipv6(inner)))
+        }
+        (false, any::Prefix::Ipv6(_)) => anyhow::bail!("IPv6 prefix given in a route: attribute"),
+        (true, any::Prefix::Ipv4(_)) => anyhow::bail!("IPv4 prefix given in a route6: attribute"),
+    }
+}
+
+/// Parse lines of Validated ROA Payload (VRP) CSV, as exported by RPKI
+/// validators, into items, each with its origin AS number, in input order.
+///
+/// Each row has `ASN`, `IP Prefix` and `Max Length` columns, and any further
+/// columns, such as the `Trust Anchor`, are ignored. A header row, with an
+/// `ASN` first column, is skipped:
+///
+/// ```text
+/// ASN,IP Prefix,Max Length,Trust Anchor
+/// AS65000,192.0.2.0/24,24,example
+/// AS65000,2001:db8::/32,48,example
+/// ```
+///
+/// A `Max Length` equal to the prefix length is taken to be implicit, since
+/// validators always write it. Whitespace, blank lines and comments are
+/// handled as by [`parse_text`].
+pub fn parse_vrp_csv<S, I, E>(
+    iter: I,
+) -> impl Iterator<Item = anyhow::Result<(u32, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    parse_vrp_csv_with(iter, HostBits::Reject)
+}
+
+/// Parse lines of Validated ROA Payload (VRP) CSV, as by [`parse_vrp_csv`],
+/// handling prefixes with host bits set as given by `host_bits`.
+pub fn parse_vrp_csv_with<S, I, E>(
+    iter: I,
+    host_bits: HostBits,
+) -> impl Iterator<Item = anyhow::Result<(u32, RoaPrefixRange)>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    numbered_vrp_csv(iter, host_bits).map(|row| row.map(|(_, row)| row))
+}
+
+/// The rows of VRP CSV, as by [`parse_vrp_csv_with`], each with the
+/// one-based number of the line it was read from.
+fn numbered_vrp_csv<S, I, E>(
+    iter: I,
+    host_bits: HostBits,
+) -> impl Iterator<Item = anyhow::Result<(usize, (u32, RoaPrefixRange))>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    text_lines(iter).filter_map(move |line| {
+        line.and_then(|line| {
+            let row = line.locate(vrp_csv_row(line.content(), host_bits))?;
+            Ok(row.map(|row| (line.number, row)))
+        })
+        .transpose()
+    })
+}
+
+/// The origin AS number and item of a row of VRP CSV, or `None` for the
+/// header row.
+fn vrp_csv_row(
+    content: &str,
+    host_bits: HostBits,
+) -> anyhow::Result<Option<(u32, RoaPrefixRange)>> {
+    let mut columns = content.split(',').map(str::trim);
+    let (Some(asn), Some(prefix), Some(max_length)) =
+        (columns.next(), columns.next(), columns.next())
+    else {
+        anyhow::bail!("expected ASN, IP Prefix and Max Length columns");
+    };
+    if asn.eq_ignore_ascii_case("ASN") {
+        return Ok(None);
+    }
+    let asn = match asn.get(..2) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("AS") => &asn[2..],
+        _ => asn,
+    };
+    let asn = asn.parse().context("failed to parse origin AS number")?;
+    let max_length = Some(max_length.parse().context("failed to parse max_length")?);
+    let item = JsonRange {
+        prefix: prefix.to_string(),
+        max_length,
+    }
+    .into_range(host_bits)?;
+    Ok(Some((asn, item.collapsed())))
+}
+
+/// Collect items, each with its origin AS number, into a set of items per
+/// origin AS.
+///
+/// # Errors
+///
+/// Returns the first error in `iter`.
+pub fn group_by_asn<I>(iter: I) -> anyhow::Result<BTreeMap<u32, RoaPrefixRanges>>
+where
+    I: IntoIterator<Item = anyhow::Result<(u32, RoaPrefixRange)>>,
+{
+    iter.into_iter().try_fold(
+        BTreeMap::new(),
+        |mut groups: BTreeMap<_, RoaPrefixRanges>, item| {
+            let (asn, item) = item?;
+            groups
+                .entry(asn)
+                .or_insert_with(|| RoaPrefixRanges {
+                    ranges: BTreeMap::new(),
+                    duplicates: Vec::new(),
+                    lines: Vec::new(),
+                    segments: Vec::new(),
+                    as_id: Some(asn),
+                    signing_time: None,
+                })
+                .extend([item]);
+            Ok(groups)
+        },
+    )
+}
+
+/// Remove a `#` comment, and any whitespace preceding it, from a line of
+/// text input.
+fn strip_comment(line: &str) -> &str {
+    line.split_once('#')
+        .map_or(line, |(content, _)| content.trim_end())
+}
+
+/// Strip list punctuation, as found in prefixes copied from documentation,
+/// from a line of text input.
+///
+/// A leading `-`, `*` or `•` bullet is only removed when followed by
+/// whitespace, so that it is never confused with a `max_length` suffix.
+pub(crate) fn trim_list_markers(line: &str) -> &str {
+    let line = line.trim();
+    let line = ["-", "*", "•"]
+        .iter()
+        .find_map(|marker| {
+            line.strip_prefix(marker)
+                .filter(|rest| rest.starts_with(char::is_whitespace))
+        })
+        .map_or(line, str::trim_start);
+    line.strip_suffix(',').map_or(line, str::trim_end)
+}
+
+/// Unicode characters that are commonly substituted for `/` by word
+/// processors and rich-text editors.
+const SLASH_LOOKALIKES: [char; 3] = [
+    '\u{ff0f}', // FULLWIDTH SOLIDUS
+    '\u{2044}', // FRACTION SLASH
+    '\u{2215}', // DIVISION SLASH
+];
+
+/// Replace Unicode look-alikes of `/` in a line of text input with `/`.
+pub(crate) fn normalize_slashes(line: &str) -> Cow<'_, str> {
+    if line.contains(SLASH_LOOKALIKES) {
+        Cow::Owned(line.replace(SLASH_LOOKALIKES, "/"))
+    } else {
+        Cow::Borrowed(line)
+    }
+}
+
+/// Pass through items from input that is assumed to already be in canonical
+/// order, dropping adjacent duplicates.
+///
+/// Each item is given with its one-based line number, as by
+/// [`parse_numbered_text`]. This does not collect the items, and yields an
+/// error at the first item found to be out of order.
+pub fn dedup_sorted<I>(iter: I) -> impl Iterator<Item = anyhow::Result<RoaPrefixRange>>
+where
+    I: IntoIterator<Item = anyhow::Result<(usize, RoaPrefixRange)>>,
+{
+    let mut prev: Option<RoaPrefixRange> = None;
+    iter.into_iter().filter_map(move |item| match item {
+        Err(err) => Some(Err(err)),
+        Ok((line, item)) => match prev.map(|prev| item.cmp(&prev)) {
+            Some(Ordering::Equal) => None,
+            Some(Ordering::Less) => Some(Err(anyhow::anyhow!(
+                "line {line}: item {item:#} is out of order in input assumed to be sorted"
+            ))),
+            Some(Ordering::Greater) | None => {
+                prev = Some(item);
+                Some(Ok(item))
+            }
+        },
+    })
+}
+
+/// Find the deviations of items from the canonical form `form`, without
+/// collecting them.
+///
+/// Each item is compared only with the one preceding it, so that issues are
+/// yielded as soon as they are read, and a caller can stop at the first. Up
+/// to the first issue, this agrees with [`RoaPrefixRanges::validate_in`].
+///
+/// Each item is given with its one-based line number, as by
+/// [`parse_numbered_text`].
+pub fn stream_issues<I>(iter: I, form: CanonicalForm) -> impl Iterator<Item = anyhow::Result<Issue>>
+where
+    I: IntoIterator<Item = anyhow::Result<(usize, RoaPrefixRange)>>,
+{
+    let mut prev: Option<RoaPrefixRange> = None;
+    iter.into_iter().enumerate().flat_map(move |(index, item)| {
+        let (line, item) = match item {
+            Ok(item) => item,
+            Err(err) => return vec![Err(err)],
+        };
+        let mut issues = Vec::new();
+        if item.has_explicit_equal_max_length() {
+            issues.push(Ok(Issue::RedundantMaxLength { index, line, item }));
+        }
+        if prev == Some(item) {
+            issues.push(Ok(Issue::Duplicate { index, line, item }));
+        } else if prev.is_some_and(|prev| form.compare(&item, &prev) == Ordering::Less) {
+            issues.push(Ok(Issue::Misordered { index, line, item }));
+        }
+        prev = Some(item);
+        issues
+    })
+}
+
+/// Check whether items are in non-decreasing canonical order, without
+/// collecting them.
+///
+/// # Errors
+///
+/// Returns the zero-based index of the first item that is less than its
+/// predecessor.
+pub fn is_sorted<I>(iter: I) -> Result<(), usize>
+where
+    I: IntoIterator<Item = RoaPrefixRange>,
+{
+    let mut iter = iter.into_iter();
+    let Some(mut prev) = iter.next() else {
+        return Ok(());
+    };
+    for (i, item) in iter.enumerate() {
+        if item < prev {
+            return Err(i + 1);
+        }
+        prev = item;
+    }
+    Ok(())
+}
+
+/// Parse lines of text input, and return the items in canonical order, with
+/// duplicates and redundant `max_length` values removed.
+///
+/// # Errors
+///
+/// Returns an error if a line cannot be read or parsed.
+pub fn sort_text<S, I, E>(lines: I) -> anyhow::Result<Vec<RoaPrefixRange>>
+where
+    S: AsRef<str>,
+    I: IntoIterator<Item = Result<S, E>>,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    let mut ranges = RoaPrefixRanges::from_text(lines)?;
+    ranges.canonicalize_in_place();
+    Ok(ranges.into_iter().map(|(item, _)| item).collect())
+}
+
+/// Decode a DER-encoded ROA, and return its items in canonical order, with
+/// duplicates and redundant `max_length` values removed.
+///
+/// # Errors
+///
+/// Returns an error if the ROA cannot be decoded.
+pub fn sort_roa(bytes: &[u8]) -> anyhow::Result<Vec<RoaPrefixRange>> {
+    let mut ranges = RoaPrefixRanges::from_roa(bytes)?;
+    ranges.canonicalize_in_place();
+    Ok(ranges.into_iter().map(|(item, _)| item).collect())
+}
+
+/// Decode a DER-encoded ROA, and check that the structure of its eContent
+/// was already in canonical order: the `ipAddrBlocks` by address family, one
+/// block for each, and the `addresses` within each block.
+///
+/// # Errors
+///
+/// Returns an error if the ROA cannot be decoded, or contains invalid
+/// prefixes.
+pub fn check_roa_order(bytes: &[u8]) -> anyhow::Result<Vec<RoaOrderIssue>> {
+    let roas = Roa::from_der_concatenated(&unarmor(bytes)?)?;
+    let mut issues = Vec::new();
+    for roa in &roas {
+        issues.extend(roa_order_issues(roa)?);
+    }
+    Ok(issues)
+}
+
+/// The departures from canonical order of the structure of a single ROA.
+fn roa_order_issues(roa: &Roa) -> anyhow::Result<Vec<RoaOrderIssue>> {
+    let mut seen = Vec::new();
+    let mut issues = Vec::new();
+    for family in roa.econtent().ip_addr_blocks() {
+        let afi = family.address_family()?;
+        if seen.contains(&afi) {
+            issues.push(RoaOrderIssue::FamilyRepeated(afi));
+        } else if afi == concrete::Afi::Ipv4 && seen.contains(&concrete::Afi::Ipv6) {
+            issues.push(RoaOrderIssue::FamiliesMisordered);
+        }
+        seen.push(afi);
+        let items = family_ranges(family).collect::<anyhow::Result<Vec<_>>>()?;
+        issues.extend(address_order_issues(afi, &items));
+    }
+    Ok(issues)
+}
+
+/// The departures from canonical order of the `addresses` of a block: the
+/// first item that sorts before the one preceding it, and the first that is
+/// equal to it.
+fn address_order_issues(
+    afi: concrete::Afi,
+    items: &[RoaPrefixRange],
+) -> impl Iterator<Item = RoaOrderIssue> + '_ {
+    let misordered = items
+        .windows(2)
+        .position(|pair| pair[1] < pair[0])
+        .map(|index| RoaOrderIssue::AddressesMisordered {
+            afi,
+            index: index + 1,
+            item: items[index + 1],
+        });
+    let repeated = items
+        .windows(2)
+        .position(|pair| pair[1] == pair[0])
+        .map(|index| RoaOrderIssue::AddressRepeated {
+            afi,
+            index: index + 1,
+            item: items[index + 1],
+        });
+    misordered.into_iter().chain(repeated)
+}
+
+/// A set of [`RoaPrefixRange`]s, held in canonical order, that remembers
+/// the input position of each item and any duplicates discarded.
+///
+/// Iterating over it yields each item in canonical order, together with its
+/// zero-based input position.
+#[derive(Debug)]
+pub struct RoaPrefixRanges {
+    ranges: BTreeMap<RoaPrefixRange, usize>,
+    duplicates: Vec<(RoaPrefixRange, usize)>,
+    // the one-based line number of each item, by input position, if read
+    // from lines of text
+    lines: Vec<usize>,
+    // the input positions at which each of several concatenated ROAs after
+    // the first begins
+    segments: Vec<usize>,
+    as_id: Option<u32>,
+    signing_time: Option<DateTime<FixedOffset>>,
+}
+
+impl RoaPrefixRanges {
+    /// Parse lines of text input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read or parsed.
+    pub fn from_text<S, I, E>(iter: I) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        parse_text(iter).collect()
+    }
+
+    /// Parse lines of text input, handling prefixes with host bits set as
+    /// given by `host_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read or parsed.
+    pub fn from_text_with<S, I, E>(iter: I, host_bits: HostBits) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        parse_numbered_text_with(iter, host_bits)
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Self::from_numbered)
+    }
+
+    /// Collect items, each with the one-based number of the line it was
+    /// read from.
+    fn from_numbered<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (usize, RoaPrefixRange)>,
+    {
+        let (lines, items): (Vec<_>, Vec<_>) = iter.into_iter().unzip();
+        Self {
+            lines,
+            ..items.into_iter().collect()
+        }
+    }
+
+    /// Decode the prefix ranges from a DER-encoded ROA.
+    ///
+    /// The DER may also be base64-encoded, with or without PEM armor. It may
+    /// hold several ROAs, concatenated back to back, whose prefix ranges are
+    /// merged as by [`Self::merge`], except that [`Self::validate`] checks
+    /// the input order of each ROA on its own.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a ROA cannot be decoded, or contains invalid
+    /// prefixes, or if the input has trailing data that is not a ROA.
+    pub fn from_roa(bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_roas(Roa::from_der_concatenated(&unarmor(bytes)?)?)
+    }
+
+    /// Decode the prefix ranges from a DER-encoded ROA, after checking that
+    /// its eContent matches the message digest signed by its signer.
+    ///
+    /// The signature itself, and the certificate that it is made with, are
+    /// not verified.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ROA cannot be decoded, the message digest
+    /// does not match, or the ROA contains invalid prefixes.
+    pub fn from_verified_roa(bytes: &[u8]) -> anyhow::Result<Self> {
+        let roas = Roa::from_der_concatenated(&unarmor(bytes)?)?;
+        for (i, roa) in roas.iter().enumerate() {
+            roa.verify_digest()
+                .with_context(|| format!("failed to verify ROA {} of {}", i + 1, roas.len()))?;
+        }
+        Self::from_roas(roas)
+    }
+
+    /// Merge the prefix ranges of each of `roas`, in order.
+    fn from_roas(roas: Vec<Roa>) -> anyhow::Result<Self> {
+        let mut roas = roas.iter();
+        let first = roas.next().context("no ROA given")?;
+        roas.try_fold(first.try_into()?, |mut ranges: Self, roa| {
+            ranges
+                .segments
+                .push(ranges.ranges.len() + ranges.duplicates.len());
+            ranges.merge(roa.try_into()?);
+            Ok(ranges)
+        })
+    }
+
+    /// Parse a JSON array of objects, each with a `prefix` and an optional
+    /// `maxLength`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not such an array, or contains
+    /// invalid prefixes.
+    #[cfg(feature = "json")]
+    pub fn from_json(bytes: &[u8]) -> anyhow::Result<Self> {
+        Self::from_json_with(bytes, HostBits::Reject)
+    }
+
+    /// Parse a JSON array of objects, as by [`Self::from_json`], handling
+    /// prefixes with host bits set as given by `host_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not such an array, or contains
+    /// invalid prefixes.
+    #[cfg(feature = "json")]
+    pub fn from_json_with(bytes: &[u8], host_bits: HostBits) -> anyhow::Result<Self> {
+        let items: Vec<JsonRange> =
+            serde_json::from_slice(bytes).context("failed to parse JSON input")?;
+        items
+            .into_iter()
+            .enumerate()
+            .map(|(i, item)| {
+                item.into_range(host_bits)
+                    .with_context(|| format!("failed to parse JSON input item {}", i + 1))
+            })
+            .collect()
+    }
+
+    /// Collect already-parsed items, in input order.
+    ///
+    /// This is equivalent to collecting them with [`FromIterator`].
+    pub fn from_ranges<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = RoaPrefixRange>,
+    {
+        iter.into_iter().collect()
+    }
+
+    /// Parse the prefixes of the `route:` and `route6:` objects in RPSL
+    /// input, such as an IRR database dump, ignoring all other objects and
+    /// attributes.
+    ///
+    /// The prefix of each object is taken from its primary key. RPSL has no
+    /// notion of a `max_length`, so every item has an implicit `max_length`
+    /// equal to its prefix length.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read, or a `route:` or `route6:`
+    /// attribute does not hold a prefix of the matching address family.
+    pub fn from_rpsl<S, I, E>(iter: I) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::from_rpsl_with(iter, HostBits::Reject)
+    }
+
+    /// Parse the prefixes of RPSL input, as by [`Self::from_rpsl`], handling
+    /// prefixes with host bits set as given by `host_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read, or a `route:` or `route6:`
+    /// attribute does not hold a prefix of the matching address family.
+    pub fn from_rpsl_with<S, I, E>(iter: I, host_bits: HostBits) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        text_lines(iter)
+            .filter_map(|line| {
+                line.and_then(|line| {
+                    let item = line.locate(rpsl_route(&line, host_bits))?;
+                    Ok(item.map(|item| (line.number, item)))
+                })
+                .transpose()
+            })
+            .collect::<anyhow::Result<Vec<_>>>()
+            .map(Self::from_numbered)
+    }
+
+    /// Parse lines of Validated ROA Payload (VRP) CSV, as described for
+    /// [`parse_vrp_csv`].
+    ///
+    /// The origin AS number is kept if every row has the same.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read or parsed.
+    pub fn from_vrp_csv<S, I, E>(iter: I) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        Self::from_vrp_csv_with(iter, HostBits::Reject)
+    }
+
+    /// Parse lines of Validated ROA Payload (VRP) CSV, as by
+    /// [`Self::from_vrp_csv`], handling prefixes with host bits set as given
+    /// by `host_bits`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a line cannot be read or parsed.
+    pub fn from_vrp_csv_with<S, I, E>(iter: I, host_bits: HostBits) -> anyhow::Result<Self>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = Result<S, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let rows = numbered_vrp_csv(iter, host_bits).collect::<anyhow::Result<Vec<_>>>()?;
+        let as_id = rows
+            .first()
+            .map(|(_, (asn, _))| *asn)
+            .filter(|asn| rows.iter().all(|(_, (other, _))| other == asn));
+        let mut ranges =
+            Self::from_numbered(rows.into_iter().map(|(line, (_, item))| (line, item)));
+        ranges.as_id = as_id;
+        Ok(ranges)
+    }
+
+    /// Add the items of `other`, as if its input followed the input of these
+    /// items.
+    ///
+    /// The origin AS number and signing time are each only kept if both
+    /// sets have the same.
+    pub fn merge(&mut self, other: Self) {
+        if self.as_id != other.as_id {
+            self.as_id = None;
+        }
+        if self.signing_time != other.signing_time {
+            self.signing_time = None;
+        }
+        let offset = self.ranges.len() + self.duplicates.len();
+        if !other.lines.is_empty() {
+            let len = self.lines.len();
+            self.lines.extend((len..offset).map(|index| index + 1));
+            self.lines.extend(&other.lines);
+        }
+        self.segments
+            .extend(other.segments.iter().map(|start| offset + start));
+        self.extend(other.input_order().into_iter().map(|(_, item)| item));
+    }
+
+    /// The origin AS number, if the items were decoded from a ROA, or all
+    /// share one in VRP CSV input.
+    #[must_use]
+    pub const fn as_id(&self) -> Option<u32> {
+        self.as_id
+    }
+
+    /// The same items, with the origin AS number `as_id`.
+    #[cfg(feature = "rpki-interop")]
+    pub(crate) const fn with_as_id(self, as_id: u32) -> Self {
+        Self {
+            as_id: Some(as_id),
+            ..self
+        }
+    }
+
+    /// Encode the items, in canonical order, as the DER-encoded eContent of a
+    /// ROA (a `RouteOriginAttestation`) for the origin AS `as_id`.
+    ///
+    /// Redundant `max_length` values are omitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there are no items, or the encoding fails.
+    pub fn to_econtent(&self, as_id: u32) -> anyhow::Result<Vec<u8>> {
+        anyhow::ensure!(
+            !self.ranges.is_empty(),
+            "cannot encode a ROA without any prefixes"
+        );
+        let mut blocks: Vec<(concrete::Afi, Vec<RoaIpAddress>)> = Vec::new();
+        self.ranges.keys().for_each(|item| {
+            let octets = match item {
+                RoaPrefixRange::Ipv4(inner) => inner
+                    .prefix
+                    .prefix()
+                    .into_primitive()
+                    .to_be_bytes()
+                    .to_vec(),
+                RoaPrefixRange::Ipv6(inner) => inner
+                    .prefix
+                    .prefix()
+                    .into_primitive()
+                    .to_be_bytes()
+                    .to_vec(),
+            };
+            let address = RoaIpAddress::new(&octets, item.prefix_length(), item.max_length());
+            match blocks.last_mut() {
+                Some((afi, addresses)) if *afi == item.afi() => addresses.push(address),
+                _ => blocks.push((item.afi(), vec![address])),
+            }
+        });
+        let econtent = RouteOriginAttestation::new(
+            as_id,
+            blocks
+                .into_iter()
+                .map(|(afi, addresses)| RoaIpAddressFamily::new(afi, addresses))
+                .collect(),
+        );
+        rasn::der::encode(&econtent).context("failed to encode eContent")
+    }
+
+    /// The signing time of the ROA that the items were decoded from, if it
+    /// is given.
+    #[must_use]
+    pub const fn signing_time(&self) -> Option<DateTime<FixedOffset>> {
+        self.signing_time
+    }
+
+    /// Iterate over the items in canonical order, without consuming them.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &RoaPrefixRange> + ExactSizeIterator {
+        self.ranges.keys()
+    }
+
+    /// All items in canonical order, including the duplicates discarded,
+    /// together with their input positions.
+    ///
+    /// Duplicates follow the item they duplicate, in input order. Only
+    /// duplicates of items still held are included.
+    #[must_use]
+    pub fn with_duplicates(&self) -> Vec<(RoaPrefixRange, usize)> {
+        let mut entries: Vec<_> = self
+            .ranges
+            .iter()
+            .map(|(item, i)| (*item, *i))
+            .chain(
+                self.duplicates
+                    .iter()
+                    .filter(|(item, _)| self.ranges.contains_key(item))
+                    .copied(),
+            )
+            .collect();
+        entries.sort_unstable_by(|(a, i), (b, j)| a.cmp(b).then(i.cmp(j)));
+        entries
+    }
+
+    /// The number of distinct items.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Whether there are no items.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// The items in canonical order, without duplicates, and with any
+    /// redundant `max_length` values collapsed.
+    #[must_use]
+    pub fn canonical(&self) -> Vec<RoaPrefixRange> {
+        self.ranges.keys().map(|item| item.collapsed()).collect()
+    }
+
+    /// Render all items in canonical order as text, one per line, collapsing
+    /// any redundant `max_length` values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let lines = ["2001:db8::/32", "192.0.2.0/24-24", "10.0.0.0/8-16"];
+    /// let ranges = roasort::RoaPrefixRanges::from_text(lines.map(Ok::<_, std::io::Error>))?;
+    /// assert_eq!(
+    ///     ranges.to_text_string(),
+    ///     "10.0.0.0/8-16\n192.0.2.0/24\n2001:db8::/32\n"
+    /// );
+    /// # Ok::<_, anyhow::Error>(())
+    /// ```
+    #[must_use]
+    pub fn to_text_string(&self) -> String {
+        self.ranges.keys().fold(String::new(), |mut text, item| {
+            text.push_str(&item.to_string());
+            text.push('\n');
+            text
+        })
+    }
+
+    /// Discard duplicates and redundant `max_length` values, and re-number
+    /// the retained items in canonical order.
+    ///
+    /// After this, [`Self::validate`] reports no issues. Calling it again has
+    /// no further effect.
+    pub fn canonicalize_in_place(&mut self) {
+        self.duplicates.clear();
+        self.lines.clear();
+        self.segments.clear();
+        self.ranges = std::mem::take(&mut self.ranges)
+            .into_keys()
+            .enumerate()
+            .map(|(i, item)| (item.collapsed(), i))
+            .collect();
+    }
+
+    /// The items only in this set, and those only in `other`, each in
+    /// canonical order.
+    #[must_use]
+    pub fn symmetric_difference(&self, other: &Self) -> (Vec<RoaPrefixRange>, Vec<RoaPrefixRange>) {
+        let (mut only_self, mut only_other) = (Vec::new(), Vec::new());
+        let mut left = self.ranges.keys().peekable();
+        let mut right = other.ranges.keys().peekable();
+        // both are in canonical order, so a single merge pass suffices
+        loop {
+            match (left.peek(), right.peek()) {
+                (Some(a), Some(b)) => match a.cmp(b) {
+                    Ordering::Less => only_self.extend(left.next()),
+                    Ordering::Greater => only_other.extend(right.next()),
+                    Ordering::Equal => {
+                        _ = left.next();
+                        _ = right.next();
+                    }
+                },
+                (Some(_), None) => only_self.extend(left.by_ref()),
+                (None, Some(_)) => only_other.extend(right.by_ref()),
+                (None, None) => break,
+            }
+        }
+        (only_self, only_other)
+    }
+
+    /// The items that are not also in `other`.
+    #[must_use]
+    pub fn difference(&self, other: &Self) -> Self {
+        self.filtered(|item| !other.ranges.contains_key(item))
+    }
+
+    /// The items that are not covered by any item in `other`, in the sense
+    /// of [`RoaPrefixRange::covers`].
+    #[must_use]
+    pub fn uncovered_by(&self, other: &Self) -> Self {
+        self.filtered(|item| !other.ranges.keys().any(|excluded| excluded.covers(item)))
+    }
+
+    fn filtered<F>(&self, keep: F) -> Self
+    where
+        F: Fn(&RoaPrefixRange) -> bool,
+    {
+        Self {
+            ranges: self
+                .ranges
+                .iter()
+                .filter(|(item, _)| keep(item))
+                .map(|(item, i)| (*item, *i))
+                .collect(),
+            duplicates: self
+                .duplicates
+                .iter()
+                .filter(|(item, _)| keep(item))
+                .copied()
+                .collect(),
+            lines: self.lines.clone(),
+            segments: self.segments.clone(),
+            as_id: self.as_id,
+            signing_time: self.signing_time,
+        }
+    }
+
+    /// Take each implicit `max_length` to be the maximum prefix length of the
+    /// address family (`/32` or `/128`), rather than the prefix length, as
+    /// some tooling does.
+    ///
+    /// This affects ordering, de-duplication and output alike: for example,
+    /// `10.0.0.0/8` becomes `10.0.0.0/8-32`, a duplicate of any such item
+    /// written explicitly. An explicit `max_length` equal to the prefix
+    /// length is then the only way to authorize the prefix alone, so it is
+    /// kept as it is.
+    pub fn expand_implicit_max_length(&mut self) {
+        let entries = self.input_order();
+        self.ranges.clear();
+        self.duplicates.clear();
+        // the last occurrence of a duplicated item is retained
+        entries.into_iter().for_each(|(i, item)| {
+            let item = item.with_host_max_length();
+            if let Some(duplicate) = self.ranges.remove_entry(&item) {
+                self.duplicates.push(duplicate);
+            }
+            _ = self.ranges.insert(item, i);
+        });
+    }
+
+    /// Keep only the items, and the duplicates, in the address family `afi`.
+    ///
+    /// Since the input position of each item is kept, validation afterwards
+    /// compares only the items kept, but reports their original positions.
+    pub fn retain_afi(&mut self, afi: concrete::Afi) {
+        self.ranges.retain(|item, _| item.afi() == afi);
+        self.duplicates.retain(|(item, _)| item.afi() == afi);
+    }
+
+    /// Compute the changes that turn `old` into `self`, in canonical order.
+    ///
+    /// Where exactly one entry for a prefix was removed and exactly one
+    /// added, the pair is reported as a [`Change::MaxLengthChanged`].
+    #[must_use]
+    pub fn changes_from(&self, old: &Self) -> Vec<Change> {
+        let mut groups: BTreeMap<RoaPrefixRange, (Vec<RoaPrefixRange>, Vec<RoaPrefixRange>)> =
+            BTreeMap::new();
+        old.ranges
+            .keys()
+            .filter(|item| !self.ranges.contains_key(item))
+            .for_each(|item| {
+                groups
+                    .entry(item.without_max_length())
+                    .or_default()
+                    .0
+                    .push(*item)
+            });
+        self.ranges
+            .keys()
+            .filter(|item| !old.ranges.contains_key(item))
+            .for_each(|item| {
+                groups
+                    .entry(item.without_max_length())
+                    .or_default()
+                    .1
+                    .push(*item)
+            });
+        let effective_max_length =
+            |item: &RoaPrefixRange| item.max_length().unwrap_or_else(|| item.prefix_length());
+        groups
+            .into_values()
+            .flat_map(
+                |(removed, added)| match (removed.as_slice(), added.as_slice()) {
+                    ([from], [to]) => vec![Change::MaxLengthChanged {
+                        prefix: from.prefix(),
+                        from: effective_max_length(from),
+                        to: effective_max_length(to),
+                    }],
+                    _ => removed
+                        .into_iter()
+                        .map(Change::Removed)
+                        .chain(added.into_iter().map(Change::Added))
+                        .collect(),
+                },
+            )
+            .collect()
+    }
+
+    /// Keep only the entry with the greatest `max_length` for each prefix,
+    /// since it authorizes everything that the others with the same prefix
+    /// do.
+    ///
+    /// Returns each entry removed, together with the entry that it was folded
+    /// into.
+    pub fn fold_max_length(&mut self) -> Vec<(RoaPrefixRange, RoaPrefixRange)> {
+        // entries with the same prefix are adjacent in canonical order, with
+        // the widest last
+        let folded: Vec<_> = self
+            .ranges
+            .keys()
+            .zip(self.ranges.keys().skip(1))
+            .filter(|(item, next)| item.prefix() == next.prefix())
+            .map(|(item, _)| *item)
+            .collect();
+        folded.iter().for_each(|item| {
+            _ = self.ranges.remove(item);
+        });
+        folded
+            .into_iter()
+            .filter_map(|item| {
+                self.ranges
+                    .range(item..)
+                    .next()
+                    .map(|(kept, _)| (item, *kept))
+            })
+            .collect()
+    }
+
+    /// Reduce the items to a minimal set that authorizes exactly the same
+    /// routes.
+    ///
+    /// An item covered by another, in the sense of [`RoaPrefixRange::covers`],
+    /// is removed. An item whose two child prefixes are both present, with
+    /// the same `max_length` greater than its own, is merged with them into
+    /// the item with that `max_length`. This is repeated until neither
+    /// applies.
+    ///
+    /// Siblings are never merged without their parent, since the parent
+    /// would authorize a route that neither of them does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a merged item cannot be constructed.
+    pub fn aggregate(&mut self) -> anyhow::Result<()> {
+        let mut raws: BTreeMap<RawRange, usize> = self
+            .ranges
+            .iter()
+            .map(|(item, i)| (item.into(), *i))
+            .collect();
+        loop {
+            let covered: Vec<_> = raws
+                .keys()
+                .filter(|raw| raw.is_covered_in(&raws))
+                .copied()
+                .collect();
+            covered.iter().for_each(|raw| {
+                _ = raws.remove(raw);
+            });
+            let mut merged = false;
+            for parent in raws.keys().copied().collect::<Vec<_>>() {
+                let Some(children) = parent.children() else {
+                    continue;
+                };
+                // with covered items removed, there is at most one item for
+                // each prefix
+                let [lower, upper] = children.map(|child| {
+                    let first = RawRange {
+                        max_length: 0,
+                        ..child
+                    };
+                    let last = RawRange {
+                        max_length: u8::MAX,
+                        ..child
+                    };
+                    raws.range(first..=last).next().map(|(item, _)| *item)
+                });
+                let (Some(lower), Some(upper)) = (lower, upper) else {
+                    continue;
+                };
+                if lower.max_length != upper.max_length || lower.max_length <= parent.max_length {
+                    continue;
+                }
+                let Some(index) = raws.remove(&parent) else {
+                    continue;
+                };
+                _ = raws.remove(&lower);
+                _ = raws.remove(&upper);
+                _ = raws.insert(
+                    RawRange {
+                        max_length: lower.max_length,
+                        ..parent
+                    },
+                    index,
+                );
+                merged = true;
+            }
+            if covered.is_empty() && !merged {
+                break;
+            }
+        }
+        // keep the original form of the items that survive unchanged
+        let originals: BTreeMap<RawRange, RoaPrefixRange> = self
+            .ranges
+            .keys()
+            .map(|item| (item.into(), *item))
+            .collect();
+        self.ranges = raws
+            .into_iter()
+            .map(|(raw, index)| {
+                let item = match originals.get(&raw) {
+                    Some(item) => *item,
+                    None => raw.to_range()?,
+                };
+                Ok((item, index))
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(())
+    }
+
+    /// All entries, including duplicates, with their input index, in input
+    /// order.
+    /// The one-based line number of the item at input position `index`, or
+    /// its one-based input position if it was not read from a line of text.
+    fn line(&self, index: usize) -> usize {
+        self.lines.get(index).copied().unwrap_or(index + 1)
+    }
+
+    fn input_order(&self) -> Vec<(usize, RoaPrefixRange)> {
+        let mut entries: Vec<_> = self
+            .ranges
+            .iter()
+            .chain(self.duplicates.iter().map(|(item, i)| (item, i)))
+            .map(|(item, i)| (*i, *item))
+            .collect();
+        entries.sort_unstable_by_key(|(i, _)| *i);
+        entries
+    }
+
+    /// Check the input order of the items against the canonical order,
+    /// returning any issues found, ordered by input position.
+    #[must_use]
+    pub fn validate(&self) -> Vec<Issue> {
+        self.validate_in(CanonicalForm::Draft)
+    }
+
+    /// Check the input order of the items against the order defined by
+    /// `form`, returning any issues found, ordered by input position.
+    #[must_use]
+    pub fn validate_in(&self, form: CanonicalForm) -> Vec<Issue> {
+        let entries = self.input_order();
+        let mut seen = BTreeSet::new();
+        let mut issues = Vec::new();
+        let mut prev: Option<RoaPrefixRange> = None;
+        entries.into_iter().for_each(|(index, item)| {
+            // each of several concatenated ROAs is checked on its own
+            if self.segments.contains(&index) {
+                seen.clear();
+                prev = None;
+            }
+            let line = self.line(index);
+            if item.has_explicit_equal_max_length() {
+                issues.push(Issue::RedundantMaxLength { index, line, item });
+            }
+            if !seen.insert(item) {
+                issues.push(Issue::Duplicate { index, line, item });
+            } else if prev.is_some_and(|prev| form.compare(&item, &prev) == Ordering::Less) {
+                issues.push(Issue::Misordered { index, line, item });
+            }
+            prev = Some(item);
+        });
+        issues
+    }
+
+    /// A line-by-line diff from the input order to the canonical order.
+    ///
+    /// The input entries kept in place are a longest run that is already in
+    /// canonical order, so the diff is as short as possible. If the input is
+    /// canonical, every line is [`DiffLine::Unchanged`].
+    #[must_use]
+    pub fn canonical_diff(&self) -> Vec<DiffLine> {
+        let input = self.input_order();
+        let canonical: Vec<_> = self.ranges.keys().map(|item| item.collapsed()).collect();
+        // an entry written differently from its canonical form is never kept
+        let ranks: Vec<_> = input
+            .iter()
+            .map(|(_, item)| {
+                (!item.has_explicit_equal_max_length())
+                    .then(|| canonical.binary_search(item).ok())
+                    .flatten()
+            })
+            .collect();
+        // find the longest strictly increasing sequence of canonical ranks,
+        // tracking the smallest final rank of a sequence of each length
+        let mut tails: Vec<usize> = Vec::new();
+        let mut prev = vec![None; input.len()];
+        ranks.iter().enumerate().for_each(|(i, rank)| {
+            if rank.is_some() {
+                let len = tails.partition_point(|&t| ranks[t] < *rank);
+                prev[i] = len.checked_sub(1).map(|l| tails[l]);
+                if len == tails.len() {
+                    tails.push(i);
+                } else {
+                    tails[len] = i;
+                }
+            }
+        });
+        let mut kept = vec![false; input.len()];
+        let mut next = tails.last().copied();
+        while let Some(i) = next {
+            kept[i] = true;
+            next = prev[i];
+        }
+        let mut canonical = canonical.into_iter().enumerate().peekable();
+        let mut lines = Vec::new();
+        input.into_iter().enumerate().for_each(|(i, (_, item))| {
+            match ranks[i].filter(|_| kept[i]) {
+                Some(rank) => {
+                    while let Some((_, added)) = canonical.next_if(|(j, _)| *j < rank) {
+                        lines.push(DiffLine::Added(added));
+                    }
+                    _ = canonical.next();
+                    lines.push(DiffLine::Unchanged(item));
+                }
+                None => lines.push(DiffLine::Removed(item)),
+            }
+        });
+        lines.extend(canonical.map(|(_, item)| DiffLine::Added(item)));
+        lines
+    }
+
+    /// Find the first position at which the input deviates from the
+    /// canonical order, if any.
+    #[must_use]
+    pub fn first_difference(&self) -> Option<FirstDifference> {
+        let mut canonical = self.ranges.keys();
+        self.input_order()
+            .into_iter()
+            .map(|(index, found)| (index, found, canonical.next().copied()))
+            .find(|(_, found, expected)| expected.as_ref() != Some(found))
+            .map(|(index, found, expected)| FirstDifference {
+                line: self.line(index),
+                found,
+                expected,
+            })
+    }
+
+    /// Find runs of sibling prefixes with equal `max_length` values that
+    /// could be aggregated into a common parent.
+    ///
+    /// Returns each such parent, together with the number of entries it
+    /// would replace. Note that the parent also authorizes the intermediate
+    /// prefix lengths, which none of the fragments do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a parent prefix cannot be constructed.
+    pub fn aggregation_candidates(&self) -> anyhow::Result<Vec<(RoaPrefixRange, usize)>> {
+        let mut candidates: BTreeMap<RawRange, usize> =
+            self.ranges.keys().map(|item| (item.into(), 1)).collect();
+        while let Some((lower, upper)) = candidates.keys().find_map(|raw| {
+            raw.sibling()
+                .filter(|sibling| raw.address < sibling.address && candidates.contains_key(sibling))
+                .map(|sibling| (*raw, sibling))
+        }) {
+            let count = candidates.remove(&lower).unwrap_or_default()
+                + candidates.remove(&upper).unwrap_or_default();
+            if let Some(parent) = lower.parent() {
+                *candidates.entry(parent).or_default() += count;
+            }
+        }
+        candidates
+            .into_iter()
+            .filter(|(_, count)| *count > 1)
+            .map(|(raw, count)| Ok((raw.to_range()?, count)))
+            .collect()
+    }
+
+    /// The IPv4 items, in canonical order.
+    #[must_use]
+    pub fn ipv4_ranges(&self) -> impl Iterator<Item = &InnerRoaPrefixRange<Ipv4>> {
+        self.ranges.keys().filter_map(|item| match item {
+            RoaPrefixRange::Ipv4(inner) => Some(inner),
+            RoaPrefixRange::Ipv6(_) => None,
+        })
+    }
+
+    /// The IPv6 items, in canonical order.
+    #[must_use]
+    pub fn ipv6_ranges(&self) -> impl Iterator<Item = &InnerRoaPrefixRange<Ipv6>> {
+        self.ranges.keys().filter_map(|item| match item {
+            RoaPrefixRange::Ipv4(_) => None,
+            RoaPrefixRange::Ipv6(inner) => Some(inner),
+        })
+    }
+
+    /// Whether any IPv4 items are present.
+    #[must_use]
+    pub fn has_ipv4(&self) -> bool {
+        matches!(self.ranges.keys().next(), Some(RoaPrefixRange::Ipv4(_)))
+    }
+
+    /// Whether any IPv6 items are present.
+    #[must_use]
+    pub fn has_ipv6(&self) -> bool {
+        matches!(
+            self.ranges.keys().next_back(),
+            Some(RoaPrefixRange::Ipv6(_))
+        )
+    }
+
+    /// Count the items, by address family, the duplicates discarded, and
+    /// the items with an explicit `max_length`.
+    #[must_use]
+    pub fn stats(&self) -> Stats {
+        let ipv4 = self
+            .ranges
+            .keys()
+            .filter(|item| item.afi() == concrete::Afi::Ipv4)
+            .count();
+        let explicit_max_length = self
+            .ranges
+            .keys()
+            .filter(|item| item.max_length().is_some() || item.has_explicit_equal_max_length())
+            .count();
+        Stats {
+            total: self.ranges.len(),
+            ipv4,
+            ipv6: self.ranges.len() - ipv4,
+            duplicates_removed: self.duplicates.len(),
+            explicit_max_length,
+        }
+    }
+
+    /// The number of items that are covered by some other item, in the
+    /// sense of [`RoaPrefixRange::covers`], and so authorize nothing extra.
+    #[must_use]
+    pub fn covered_count(&self) -> usize {
+        self.ranges
+            .keys()
+            .filter(|item| {
+                self.ranges
+                    .keys()
+                    .any(|other| other != *item && other.covers(item))
+            })
+            .count()
+    }
+
+    /// The items whose `max_length` exceeds the prefix length by more than
+    /// `limit` bits, in canonical order.
+    pub fn exceeding_expansion(&self, limit: u8) -> impl Iterator<Item = &RoaPrefixRange> {
+        self.ranges
+            .keys()
+            .filter(move |item| item.exceeds_expansion(limit))
+    }
+
+    /// The pairs of distinct items in which the first covers the second, in
+    /// the sense of [`RoaPrefixRange::covers`], in canonical order.
+    ///
+    /// Since items are ordered by prefix address, the items that an item
+    /// might cover either follow it within its prefix, or share its prefix
+    /// with a lesser `max_length` and so immediately precede it. Only those
+    /// are compared.
+    #[must_use]
+    pub fn overlaps(&self) -> Vec<(RoaPrefixRange, RoaPrefixRange)> {
+        let items: Vec<_> = self
+            .ranges
+            .keys()
+            .map(|item| (*item, RawRange::from(item)))
+            .collect();
+        let mut pairs: Vec<_> = items
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (covering, raw))| {
+                let same_prefix = items[..i].iter().rev().take_while(move |(_, other)| {
+                    other.ipv6 == raw.ipv6
+                        && other.address == raw.address
+                        && other.length == raw.length
+                });
+                let within = items[i + 1..].iter().take_while(move |(_, other)| {
+                    other.ipv6 == raw.ipv6 && other.address & raw.mask(raw.length) == raw.address
+                });
+                same_prefix
+                    .chain(within)
+                    .filter(move |(_, other)| raw.covers(other))
+                    .map(move |(covered, _)| (*covering, *covered))
+            })
+            .collect();
+        pairs.sort_unstable();
+        pairs
+    }
+
+    /// The number of items with each prefix length, in the address family
+    /// `afi`.
+    #[must_use]
+    pub fn prefix_length_histogram(&self, afi: concrete::Afi) -> BTreeMap<u8, usize> {
+        self.ranges.keys().filter(|item| item.afi() == afi).fold(
+            BTreeMap::new(),
+            |mut histogram, item| {
+                *histogram.entry(item.prefix_length()).or_default() += 1;
+                histogram
+            },
+        )
+    }
+
+    /// Summarize the items in each address family present.
+    #[must_use]
+    pub fn afi_summary(&self) -> Vec<AfiSummary> {
+        let mut summaries: Vec<AfiSummary> = Vec::new();
+        self.ranges
+            .keys()
+            .for_each(|item| match summaries.last_mut() {
+                Some(summary) if summary.afi == item.afi() => {
+                    summary.count += 1;
+                    summary.last = *item;
+                }
+                _ => summaries.push(AfiSummary {
+                    afi: item.afi(),
+                    count: 1,
+                    first: *item,
+                    last: *item,
+                }),
+            });
+        summaries
+    }
+}
+
+/// A deviation of the input from its canonical form.
+///
+/// The `index` of each variant is the zero-based position of the offending
+/// item in the input, and the `line` the one-based number of the line of
+/// text it was read from, which also counts any blank lines and comments.
+/// For input not read from lines of text, the `line` is the one-based
+/// position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Issue {
+    /// The item is less than the one preceding it.
+    Misordered {
+        /// The zero-based input position of the item.
+        index: usize,
+        /// The one-based line number of the item.
+        line: usize,
+        /// The offending item.
+        item: RoaPrefixRange,
+    },
+    /// The item is equal to one preceding it.
+    Duplicate {
+        /// The zero-based input position of the item.
+        index: usize,
+        /// The one-based line number of the item.
+        line: usize,
+        /// The offending item.
+        item: RoaPrefixRange,
+    },
+    /// The item has an explicit `max_length` equal to its prefix length.
+    RedundantMaxLength {
+        /// The zero-based input position of the item.
+        index: usize,
+        /// The one-based line number of the item.
+        line: usize,
+        /// The offending item.
+        item: RoaPrefixRange,
+    },
+}
+
+impl Issue {
+    /// The zero-based input position of the offending item.
+    #[must_use]
+    pub const fn index(&self) -> usize {
+        match self {
+            Self::Misordered { index, .. }
+            | Self::Duplicate { index, .. }
+            | Self::RedundantMaxLength { index, .. } => *index,
+        }
+    }
+
+    /// The one-based line number of the offending item.
+    #[must_use]
+    pub const fn line(&self) -> usize {
+        match self {
+            Self::Misordered { line, .. }
+            | Self::Duplicate { line, .. }
+            | Self::RedundantMaxLength { line, .. } => *line,
+        }
+    }
+
+    /// The offending item.
+    #[must_use]
+    pub const fn item(&self) -> &RoaPrefixRange {
+        match self {
+            Self::Misordered { item, .. }
+            | Self::Duplicate { item, .. }
+            | Self::RedundantMaxLength { item, .. } => item,
+        }
+    }
+
+    /// A short, stable name for the kind of issue.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::Misordered { .. } => "misordered",
+            Self::Duplicate { .. } => "duplicate",
+            Self::RedundantMaxLength { .. } => "redundant-maxlength",
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let line = self.line();
+        let item = self.item();
+        match self {
+            Self::Misordered { .. } => write!(f, "line {line}: item {item:#} is mis-ordered"),
+            Self::Duplicate { .. } => write!(f, "line {line}: item {item:#} is a duplicate"),
+            Self::RedundantMaxLength { .. } => write!(
+                f,
+                "line {line}: item {item:#} has unnecessarily specified max_length"
+            ),
+        }
+    }
+}
+
+/// Serializes as an object with the one-based `line` of the item, the
+/// [`kind`](Issue::kind) of issue, and the `value` of the item as given.
+#[cfg(feature = "serde")]
+impl Serialize for Issue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Issue", 3)?;
+        state.serialize_field("line", &self.line())?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("value", &format!("{:#}", self.item()))?;
+        state.end()
+    }
+}
+
+/// The number of items in an address family, and the first and last of
+/// them in canonical order.
+#[derive(Debug, Clone, Copy)]
+pub struct AfiSummary {
+    afi: concrete::Afi,
+    count: usize,
+    first: RoaPrefixRange,
+    last: RoaPrefixRange,
+}
+
+impl fmt::Display for AfiSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let afi = match self.afi {
+            concrete::Afi::Ipv4 => "ipv4",
+            concrete::Afi::Ipv6 => "ipv6",
+        };
+        write!(
+            f,
+            "{afi}: {} entries, first {}, last {}",
+            self.count, self.first, self.last
+        )
+    }
+}
+
+/// A departure of the structure of a ROA's eContent from canonical order, as
+/// found by [`check_roa_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoaOrderIssue {
+    /// An IPv4 block follows an IPv6 block in `ipAddrBlocks`.
+    FamiliesMisordered,
+    /// More than one block in `ipAddrBlocks` has this address family.
+    FamilyRepeated(concrete::Afi),
+    /// The `addresses` of a block are not in canonical order.
+    AddressesMisordered {
+        /// The address family of the block.
+        afi: concrete::Afi,
+        /// The zero-based position within the block of the first item that
+        /// does not follow the one before it.
+        index: usize,
+        /// That item.
+        item: RoaPrefixRange,
+    },
+    /// An item of the `addresses` of a block is equal to the one before it.
+    AddressRepeated {
+        /// The address family of the block.
+        afi: concrete::Afi,
+        /// The zero-based position within the block of the repeated item.
+        index: usize,
+        /// That item.
+        item: RoaPrefixRange,
+    },
+}
+
+impl fmt::Display for RoaOrderIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = |afi: &concrete::Afi| match afi {
+            concrete::Afi::Ipv4 => "IPv4",
+            concrete::Afi::Ipv6 => "IPv6",
+        };
+        match self {
+            Self::FamiliesMisordered => {
+                write!(
+                    f,
+                    "ipAddrBlocks are not in canonical order: IPv6 before IPv4"
+                )
+            }
+            Self::FamilyRepeated(afi) => write!(
+                f,
+                "the {} address family is repeated in ipAddrBlocks",
+                name(afi)
+            ),
+            Self::AddressesMisordered { afi, index, item } => write!(
+                f,
+                "{} addresses are not in canonical order, from position {} ({item:#})",
+                name(afi),
+                index + 1
+            ),
+            Self::AddressRepeated { afi, index, item } => write!(
+                f,
+                "{} addresses are not in canonical order, repeated at position {} ({item:#})",
+                name(afi),
+                index + 1
+            ),
+        }
+    }
+}
+
+impl RoaOrderIssue {
+    /// A short, stable name for the kind of issue.
+    #[must_use]
+    pub const fn kind(&self) -> &'static str {
+        match self {
+            Self::FamiliesMisordered => "families-misordered",
+            Self::FamilyRepeated(_) => "family-repeated",
+            Self::AddressesMisordered { .. } => "addresses-misordered",
+            Self::AddressRepeated { .. } => "address-repeated",
+        }
+    }
+}
+
+/// Serializes as an object with the [`kind`](RoaOrderIssue::kind) of issue,
+/// the `afi` of the block concerned, if any, and for an issue within a
+/// block, the one-based `position` and the `value` of the item.
+#[cfg(feature = "serde")]
+impl Serialize for RoaOrderIssue {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let name = |afi: &concrete::Afi| match afi {
+            concrete::Afi::Ipv4 => "ipv4",
+            concrete::Afi::Ipv6 => "ipv6",
+        };
+        let mut state = serializer.serialize_struct("RoaOrderIssue", 4)?;
+        state.serialize_field("kind", self.kind())?;
+        match self {
+            Self::FamiliesMisordered => {}
+            Self::FamilyRepeated(afi) => state.serialize_field("afi", name(afi))?,
+            Self::AddressesMisordered { afi, index, item }
+            | Self::AddressRepeated { afi, index, item } => {
+                state.serialize_field("afi", name(afi))?;
+                state.serialize_field("position", &(index + 1))?;
+                state.serialize_field("value", &format!("{item:#}"))?;
+            }
+        }
+        state.end()
+    }
+}
+
+/// A policy for the canonical ordering of items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CanonicalForm {
+    /// The ordering defined by `draft-ietf-sidrops-rfc6482bis`: IPv4 before
+    /// IPv6, then numerically by address, prefix length and `max_length`.
+    Draft,
+    /// The textual form of the prefix, compared as a string, then the
+    /// numeric `max_length`, as used by some older tooling.
+    Legacy,
+}
+
+impl CanonicalForm {
+    /// Compare two items according to this ordering.
+    #[must_use]
+    pub fn compare(self, a: &RoaPrefixRange, b: &RoaPrefixRange) -> Ordering {
+        match self {
+            Self::Draft => a.cmp(b),
+            Self::Legacy => {
+                let effective_max_length = |item: &RoaPrefixRange| {
+                    item.max_length().unwrap_or_else(|| item.prefix_length())
+                };
+                a.prefix()
+                    .to_string()
+                    .cmp(&b.prefix().to_string())
+                    .then_with(|| effective_max_length(a).cmp(&effective_max_length(b)))
+            }
+        }
+    }
+}
+
+/// A single difference between two sets of ranges.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Change {
+    /// An entry present only in the new set.
+    Added(RoaPrefixRange),
+    /// An entry present only in the old set.
+    Removed(RoaPrefixRange),
+    /// The only entry for a prefix has a different (effective) `max_length`
+    /// in the new set.
+    MaxLengthChanged {
+        /// The prefix of the entry.
+        prefix: any::Prefix,
+        /// The effective `max_length` in the old set.
+        from: u8,
+        /// The effective `max_length` in the new set.
+        to: u8,
+    },
+}
+
+/// A line of a diff from the input order to the canonical order.
+///
+/// Formats as a line of a unified diff.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine {
+    /// An entry that is in the same relative position in both.
+    Unchanged(RoaPrefixRange),
+    /// An entry to be removed from its input position, as written.
+    Removed(RoaPrefixRange),
+    /// An entry to be inserted at its canonical position.
+    Added(RoaPrefixRange),
+}
+
+impl fmt::Display for DiffLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unchanged(item) => write!(f, " {item}"),
+            Self::Removed(item) => write!(f, "-{item:#}"),
+            Self::Added(item) => write!(f, "+{item}"),
+        }
+    }
+}
+
+/// The first entry in the input that is not where the canonical order would
+/// place it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirstDifference {
+    line: usize,
+    found: RoaPrefixRange,
+    expected: Option<RoaPrefixRange>,
+}
+
+impl fmt::Display for FirstDifference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: found {:#}, ", self.line, self.found)?;
+        match self.expected {
+            Some(expected) => write!(f, "expected {expected}"),
+            None => write!(f, "expected end of input"),
+        }
+    }
+}
+
+/// Counts of the entries read into a [`RoaPrefixRanges`].
+///
+/// Serializes as an object with the `total`, `ipv4`, `ipv6` and
+/// `duplicatesRemoved` counts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(rename_all = "camelCase"))]
+pub struct Stats {
+    /// The number of distinct items.
+    pub total: usize,
+    /// The number of distinct IPv4 items.
+    pub ipv4: usize,
+    /// The number of distinct IPv6 items.
+    pub ipv6: usize,
+    /// The number of duplicate items discarded.
+    pub duplicates_removed: usize,
+    /// The number of distinct items with an explicitly specified
+    /// `max_length`, including one equal to the prefix length.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub explicit_max_length: usize,
+}
+
+impl fmt::Display for Stats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} entries ({} IPv4, {} IPv6), {} duplicates removed, {} with explicit max_length",
+            self.total, self.ipv4, self.ipv6, self.duplicates_removed, self.explicit_max_length
+        )
+    }
+}
+
+impl FromIterator<RoaPrefixRange> for RoaPrefixRanges {
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = RoaPrefixRange>,
+    {
+        let mut ranges = Self {
+            ranges: BTreeMap::new(),
+            duplicates: Vec::new(),
+            lines: Vec::new(),
+            segments: Vec::new(),
+            as_id: None,
+            signing_time: None,
+        };
+        ranges.extend(iter);
+        ranges
+    }
+}
+
+impl Extend<RoaPrefixRange> for RoaPrefixRanges {
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = RoaPrefixRange>,
+    {
+        let offset = self.ranges.len() + self.duplicates.len();
+        // the last occurrence of a duplicated item is retained
+        iter.into_iter().enumerate().for_each(|(i, item)| {
+            if let Some(duplicate) = self.ranges.remove_entry(&item) {
+                self.duplicates.push(duplicate);
+            }
+            _ = self.ranges.insert(item, offset + i);
+        });
+    }
+}
+
+impl IntoIterator for RoaPrefixRanges {
+    type Item = (RoaPrefixRange, usize);
+    type IntoIter = <BTreeMap<RoaPrefixRange, usize> as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter()
+    }
+}
+
+/// Decode the items of a single address family block of a ROA, in wire
+/// order.
+fn family_ranges(
+    family: &RoaIpAddressFamily,
+) -> impl Iterator<Item = anyhow::Result<RoaPrefixRange>> + '_ {
+    let afi = family.address_family();
+    family.addresses().map(move |roa_ip_addr| match &afi {
+        Ok(concrete::Afi::Ipv4) => Ok(RoaPrefixRange::Ipv4(InnerRoaPrefixRange::new(
+            roa_ip_addr.address()?,
+            roa_ip_addr.max_length::<Ipv4>()?,
+        )?)),
+        Ok(concrete::Afi::Ipv6) => Ok(RoaPrefixRange::Ipv6(InnerRoaPrefixRange::new(
+            roa_ip_addr.address()?,
+            roa_ip_addr.max_length::<Ipv6>()?,
+        )?)),
+        Err(_) => anyhow::bail!("invalid IP address family indicator"),
+    })
+}
+
+impl TryFrom<&Roa> for RoaPrefixRanges {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &Roa) -> Result<Self, Self::Error> {
+        value
+            .econtent()
+            .ip_addr_blocks()
+            .flat_map(family_ranges)
+            .collect::<Result<Self, _>>()
+            .and_then(|ranges| {
+                let signing_time = value.signing_time().unwrap_or_else(|err| {
+                    log::warn!("ignoring unreadable signing time: {err:#}");
+                    None
+                });
+                Ok(Self {
+                    as_id: Some(value.as_id()?),
+                    signing_time,
+                    ..ranges
+                })
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_concatenated_roas() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_roa(include_bytes!("../tests/data/concatenated.roa"))?;
+        assert_eq!(ranges.as_id(), Some(65000));
+        assert_eq!(ranges.len(), 13);
+        assert_eq!(ranges.stats().duplicates_removed, 11);
+        // each ROA is in canonical order on its own
+        assert_eq!(ranges.validate(), []);
+        assert!(ranges
+            .iter()
+            .any(|item| item.to_string() == "198.51.100.0/24"));
+        // the second ROA's eContent was altered after signing
+        let err =
+            RoaPrefixRanges::from_verified_roa(include_bytes!("../tests/data/concatenated.roa"))
+                .unwrap_err();
+        assert_eq!(err.to_string(), "failed to verify ROA 2 of 2");
+        Ok(())
+    }
+
+    #[test]
+    fn check_roa_order() -> anyhow::Result<()> {
+        assert_eq!(
+            super::check_roa_order(include_bytes!("../tests/data/ok.roa"))?,
+            []
+        );
+        assert_eq!(
+            super::check_roa_order(include_bytes!("../tests/data/ok.pem"))?,
+            []
+        );
+        let issues = super::check_roa_order(include_bytes!("../tests/data/err.roa"))?;
+        assert_eq!(
+            issues,
+            [
+                RoaOrderIssue::AddressesMisordered {
+                    afi: concrete::Afi::Ipv4,
+                    index: 1,
+                    item: "192.0.2.0/24".parse()?,
+                },
+                RoaOrderIssue::AddressesMisordered {
+                    afi: concrete::Afi::Ipv6,
+                    index: 2,
+                    item: "2001:db8:1::/48".parse()?,
+                },
+            ]
+        );
+        assert_eq!(
+            issues[0].to_string(),
+            "IPv4 addresses are not in canonical order, from position 2 (192.0.2.0/24)"
+        );
+        assert!(super::check_roa_order(b"not a ROA").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn address_order_issues() -> anyhow::Result<()> {
+        let parse = |items: &[&str]| {
+            items
+                .iter()
+                .map(|item| item.parse())
+                .collect::<anyhow::Result<Vec<RoaPrefixRange>>>()
+        };
+        let afi = concrete::Afi::Ipv4;
+        let items = parse(&["10.0.0.0/8", "10.0.0.0/8-16", "192.0.2.0/24"])?;
+        assert_eq!(super::address_order_issues(afi, &items).count(), 0);
+        // an explicit max_length equal to the prefix length is the same item
+        let items = parse(&["10.0.0.0/8", "10.0.0.0/8-8", "192.0.2.0/24"])?;
+        let issues: Vec<_> = super::address_order_issues(afi, &items).collect();
+        assert_eq!(
+            issues,
+            [RoaOrderIssue::AddressRepeated {
+                afi,
+                index: 1,
+                item: "10.0.0.0/8".parse()?,
+            }]
+        );
+        assert_eq!(issues[0].kind(), "address-repeated");
+        assert_eq!(
+            issues[0].to_string(),
+            "IPv4 addresses are not in canonical order, repeated at position 2 (10.0.0.0/8-8)"
+        );
+        let items = parse(&["192.0.2.0/24", "10.0.0.0/8", "10.0.0.0/8"])?;
+        assert_eq!(
+            super::address_order_issues(afi, &items).collect::<Vec<_>>(),
+            [
+                RoaOrderIssue::AddressesMisordered {
+                    afi,
+                    index: 1,
+                    item: "10.0.0.0/8".parse()?,
+                },
+                RoaOrderIssue::AddressRepeated {
+                    afi,
+                    index: 2,
+                    item: "10.0.0.0/8".parse()?,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_roa_order_issue() -> anyhow::Result<()> {
+        assert_eq!(
+            serde_json::to_string(&RoaOrderIssue::AddressesMisordered {
+                afi: concrete::Afi::Ipv4,
+                index: 1,
+                item: "192.0.2.0/24".parse()?,
+            })?,
+            r#"{"kind":"addresses-misordered","afi":"ipv4","position":2,"value":"192.0.2.0/24"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&RoaOrderIssue::FamiliesMisordered)?,
+            r#"{"kind":"families-misordered"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&RoaOrderIssue::AddressRepeated {
+                afi: concrete::Afi::Ipv6,
+                index: 3,
+                item: "2001:db8::/32-48".parse()?,
+            })?,
+            r#"{"kind":"address-repeated","afi":"ipv6","position":4,"value":"2001:db8::/32-48"}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn roa_order_issue_display() {
+        assert_eq!(
+            RoaOrderIssue::FamiliesMisordered.to_string(),
+            "ipAddrBlocks are not in canonical order: IPv6 before IPv4"
+        );
+        assert_eq!(
+            RoaOrderIssue::FamilyRepeated(concrete::Afi::Ipv6).to_string(),
+            "the IPv6 address family is repeated in ipAddrBlocks"
+        );
+    }
+
+    #[test]
+    fn sort_text_and_roa() -> anyhow::Result<()> {
+        let text = include_str!("../tests/data/err.txt");
+        let expect: Vec<_> = include_str!("../tests/data/ok.txt").lines().collect();
+        let render = |items: Vec<RoaPrefixRange>| -> Vec<_> {
+            items.iter().map(|item| format!("{item:#}")).collect()
+        };
+        assert_eq!(
+            render(sort_text(text.lines().map(Ok::<_, std::io::Error>))?),
+            expect
+        );
+        assert_eq!(
+            render(sort_roa(include_bytes!("../tests/data/err.roa"))?),
+            expect
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn as_id() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_roa(include_bytes!("../tests/data/ok.roa"))?;
+        assert_eq!(ranges.as_id(), Some(65000));
+        let excluded = ranges.difference(&std::iter::empty().collect());
+        assert_eq!(excluded.as_id(), Some(65000));
+        let text = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8")])?;
+        assert_eq!(text.as_id(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_asn_text() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("AS65001 192.0.2.0/24"),
+            Ok("AS65000:"),
+            Ok("# comment"),
+            Ok("  198.51.100.0/24"),
+            Ok("as65002: 2001:db8::/32-48"),
+            Ok("10.0.0.0/8"),
+        ];
+        let output = super::parse_asn_text(input)
+            .map(|item| item.map(|(asn, item)| format!("AS{asn} {item}")))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(
+            output,
+            vec![
+                "AS65001 192.0.2.0/24",
+                "AS65000 198.51.100.0/24",
+                "AS65002 2001:db8::/32-48",
+                "AS65000 10.0.0.0/8",
+            ]
+        );
+        let err = super::parse_asn_text([Ok::<_, std::io::Error>("10.0.0.0/8")])
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 1: '10.0.0.0/8': no origin AS given for item"
+        );
+        assert!(
+            super::parse_asn_text([Ok::<_, std::io::Error>("AS65000/10.0.0.0/8")])
+                .collect::<anyhow::Result<Vec<_>>>()
+                .is_err()
+        );
+        assert!(
+            super::parse_asn_text([Ok::<_, std::io::Error>("AS4294967296 10.0.0.0/8")])
+                .collect::<anyhow::Result<Vec<_>>>()
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn group_by_asn() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("AS65001 2001:db8::/32"),
+            Ok("AS65000 192.0.2.0/24"),
+            Ok("AS65001 10.0.0.0/8"),
+            Ok("AS65001 2001:db8::/32"),
+        ];
+        let groups = super::group_by_asn(super::parse_asn_text(input))?;
+        let output: Vec<_> = groups
+            .iter()
+            .map(|(asn, ranges)| (*asn, ranges.as_id(), ranges.to_text_string()))
+            .collect();
+        assert_eq!(
+            output,
+            vec![
+                (65000, Some(65000), "192.0.2.0/24\n".to_string()),
+                (
+                    65001,
+                    Some(65001),
+                    "10.0.0.0/8\n2001:db8::/32\n".to_string()
+                ),
+            ]
+        );
+        assert_eq!(groups[&65001].stats().duplicates_removed, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_vrp_csv() -> anyhow::Result<()> {
+        let input = [
+            "ASN,IP Prefix,Max Length,Trust Anchor",
+            "AS65000,192.0.2.0/24,24,example",
+            "",
+            "as65001, 2001:db8::/32, 48",
+            "65002,10.0.0.0/8,16,example,extra  # comment",
+        ];
+        let rows = super::parse_vrp_csv(input.map(Ok::<_, std::io::Error>))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(
+            rows,
+            [
+                (65000, "192.0.2.0/24".parse()?),
+                (65001, "2001:db8::/32-48".parse()?),
+                (65002, "10.0.0.0/8-16".parse()?),
+            ]
+        );
+        assert!(!rows[0].1.has_explicit_equal_max_length());
+        for (input, message) in [
+            (
+                "AS65000,192.0.2.0/24",
+                "expected ASN, IP Prefix and Max Length columns",
+            ),
+            ("ASx,192.0.2.0/24,24", "failed to parse origin AS number"),
+            ("AS65000,192.0.2.0/24,", "failed to parse max_length"),
+            ("AS65000,192.0.2.0/24,23", "less than prefix length"),
+            ("AS65000,192.0.2.0/24,33", "max_length"),
+        ] {
+            let err = super::parse_vrp_csv([Ok::<_, std::io::Error>(input)])
+                .collect::<anyhow::Result<Vec<_>>>()
+                .unwrap_err();
+            assert!(format!("{err:#}").contains(message), "{input}: {err:#}");
+            assert!(err.to_string().starts_with("line 1: "), "{input}: {err}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_vrp_csv() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_vrp_csv(
+            include_str!("../tests/data/ok.csv")
+                .lines()
+                .map(Ok::<_, std::io::Error>),
+        )?;
+        assert_eq!(ranges.as_id(), Some(65000));
+        assert_eq!(
+            ranges.to_text_string(),
+            include_str!("../tests/data/ok.txt")
+        );
+        assert!(ranges.validate().is_empty());
+        let ranges = RoaPrefixRanges::from_vrp_csv([
+            Ok::<_, std::io::Error>("AS65000,192.0.2.0/24,24"),
+            Ok("AS65001,192.0.2.0/24,24"),
+        ])?;
+        assert_eq!(ranges.as_id(), None);
+        assert_eq!(ranges.stats().duplicates_removed, 1);
+        Ok(())
+    }
+
+    #[test]
+    fn from_rpsl() -> anyhow::Result<()> {
+        let input = [
+            "% whois output",
+            "route:      192.0.2.0/24",
+            "descr:      route: 10.0.0.0/8",
+            "origin:     AS65000",
+            "",
+            "ROUTE6:     2001:db8::/32  # comment",
+            "remarks:    continued",
+            "            route: 10.0.0.0/8",
+            "+           route: 10.0.0.0/8",
+            "",
+            "route:      198.51.100.0/24",
+        ];
+        let ranges = RoaPrefixRanges::from_rpsl(input.map(Ok::<_, std::io::Error>))?;
+        assert_eq!(
+            ranges.to_text_string(),
+            "192.0.2.0/24\n198.51.100.0/24\n2001:db8::/32\n"
+        );
+        assert!(ranges.validate().is_empty());
+        let err = RoaPrefixRanges::from_rpsl([Ok::<_, std::io::Error>("route: 2001:db8::/32")])
+            .unwrap_err();
+        assert_eq!(
+            format!("{err:#}"),
+            "line 1: 'route: 2001:db8::/32': IPv6 prefix given in a route: attribute"
+        );
+        assert!(
+            RoaPrefixRanges::from_rpsl([Ok::<_, std::io::Error>("route6: 2001:db8::/32-48")])
+                .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn comments_and_blank_lines() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("# customer allocations"),
+            Ok("10.0.0.0/8-16 # aggregate"),
+            Ok(""),
+            Ok("   "),
+            Ok("  # indented comment"),
+            Ok("2001:db8::/32#no space"),
+            Ok("192.0.2.0/24"),
+        ];
+        let ranges = RoaPrefixRanges::from_text(input)?;
+        assert_eq!(
+            ranges.to_text_string(),
+            "10.0.0.0/8-16\n192.0.2.0/24\n2001:db8::/32\n"
+        );
+        let positions: Vec<_> = ranges.into_iter().map(|(_, i)| i).collect();
+        assert_eq!(positions, vec![0, 2, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_error_location() {
+        let input = vec![
+            Ok::<_, std::io::Error>("# header"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/33  # too long"),
+        ];
+        let err = RoaPrefixRanges::from_text(input).unwrap_err();
+        assert_eq!(err.to_string(), "line 3: '10.0.0.0/33  # too long'");
+    }
+
+    #[test]
+    fn with_duplicates() -> anyhow::Result<()> {
+        let mut ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/8-8"),
+            Ok("10.0.0.0/8"),
+        ])?;
+        assert_eq!(ranges.len(), 2);
+        let entries: Vec<_> = ranges
+            .with_duplicates()
+            .into_iter()
+            .map(|(item, i)| (format!("{item:#}"), i))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("10.0.0.0/8".to_string(), 1),
+                ("10.0.0.0/8-8".to_string(), 3),
+                ("10.0.0.0/8".to_string(), 4),
+                ("2001:db8::/32".to_string(), 0),
+                ("2001:db8::/32".to_string(), 2),
+            ]
+        );
+        ranges.retain_afi(concrete::Afi::Ipv6);
+        assert_eq!(ranges.with_duplicates().len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn expand_implicit_max_length() -> anyhow::Result<()> {
+        for (input, expect) in [
+            ("10.0.0.0/8", "10.0.0.0/8-32"),
+            ("10.0.0.0/8-8", "10.0.0.0/8-8"),
+            ("10.0.0.0/8-16", "10.0.0.0/8-16"),
+            ("10.0.0.0/8-32", "10.0.0.0/8-32"),
+            ("192.0.2.1/32", "192.0.2.1/32"),
+            ("0.0.0.0/0", "0.0.0.0/0-32"),
+            ("2001:db8::/32", "2001:db8::/32-128"),
+            ("2001:db8::/32-32", "2001:db8::/32-32"),
+            ("2001:db8::/32-48", "2001:db8::/32-48"),
+            ("2001:db8::1/128", "2001:db8::1/128"),
+        ] {
+            let mut ranges = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>(input)])?;
+            ranges.expand_implicit_max_length();
+            let items: Vec<_> = ranges.iter().map(|item| format!("{item:#}")).collect();
+            assert_eq!(items, [expect], "{input}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn expand_implicit_max_length_order() -> anyhow::Result<()> {
+        let mut ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("10.0.0.0/8-32"),
+        ])?;
+        ranges.expand_implicit_max_length();
+        assert_eq!(ranges.stats().duplicates_removed, 1);
+        let items: Vec<_> = ranges
+            .into_iter()
+            .map(|(item, i)| (format!("{item:#}"), i))
+            .collect();
+        assert_eq!(
+            items,
+            [
+                ("10.0.0.0/8-8".to_string(), 1),
+                ("10.0.0.0/8-16".to_string(), 2),
+                ("10.0.0.0/8-32".to_string(), 3),
+            ]
+        );
+        let mut ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+        ])?;
+        ranges.expand_implicit_max_length();
+        assert_eq!(
+            ranges.validate(),
+            [Issue::Misordered {
+                index: 1,
+                line: 2,
+                item: "10.0.0.0/8-16".parse()?
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn merge() -> anyhow::Result<()> {
+        let mut ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+        ])?;
+        ranges.merge(RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("192.0.2.0/24"),
+            Ok("10.0.0.0/8"),
+        ])?);
+        assert_eq!(ranges.stats().duplicates_removed, 1);
+        assert_eq!(
+            ranges.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("10.0.0.0/8".parse()?, 3),
+                ("192.0.2.0/24".parse()?, 2),
+                ("2001:db8::/32".parse()?, 0),
+            ]
+        );
+        let mut roa = RoaPrefixRanges::from_roa(include_bytes!("../tests/data/ok.roa"))?;
+        roa.merge(RoaPrefixRanges::from_roa(include_bytes!(
+            "../tests/data/ok.roa"
+        ))?);
+        assert_eq!(roa.as_id(), Some(65000));
+        roa.merge(RoaPrefixRanges::from_text([Ok::<_, std::io::Error>(
+            "10.0.0.0/8",
+        )])?);
+        assert_eq!(roa.as_id(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_diff() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/16"),
+            Ok("192.0.2.0/24-24"),
+            Ok("10.0.0.0/8"),
+            Ok("2001:db8::/48"),
+        ];
+        let diff: Vec<_> = RoaPrefixRanges::from_text(input)?
+            .canonical_diff()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            diff,
+            vec![
+                " 10.0.0.0/8",
+                "-2001:db8::/32",
+                " 10.0.0.0/16",
+                "-192.0.2.0/24-24",
+                "-10.0.0.0/8",
+                "+192.0.2.0/24",
+                "+2001:db8::/32",
+                " 2001:db8::/48",
+            ]
+        );
+        let canonical = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+        ])?;
+        assert!(canonical
+            .canonical_diff()
+            .iter()
+            .all(|line| matches!(line, DiffLine::Unchanged(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn surrounding_whitespace() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("\t10.0.0.0/8-16"),
+            Ok("  192.0.2.0/24 "),
+            Ok("2001:db8::/32\t\r"),
+        ];
+        assert_eq!(
+            RoaPrefixRanges::from_text(input)?.to_text_string(),
+            "10.0.0.0/8-16\n192.0.2.0/24\n2001:db8::/32\n"
+        );
+        assert!(RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0 /8")]).is_err());
+        assert!(RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8 -16")]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn default_routes() -> anyhow::Result<()> {
+        let ipv4: RoaPrefixRange = "0.0.0.0/0".parse()?;
+        assert_eq!((ipv4.prefix_length(), ipv4.max_length()), (0, None));
+        let redundant: RoaPrefixRange = "0.0.0.0/0-0".parse()?;
+        assert!(redundant.has_explicit_equal_max_length());
+        assert_eq!(redundant, ipv4);
+        assert_eq!(redundant.to_string(), "0.0.0.0/0");
+        let ranged: RoaPrefixRange = "0.0.0.0/0-24".parse()?;
+        assert_eq!(ranged.max_length(), Some(24));
+        assert!(ranged.covers(&"10.0.0.0/8-24".parse()?));
+        assert!(!ranged.covers(&"10.0.0.0/8-25".parse()?));
+        assert!("0.0.0.0/0-33".parse::<RoaPrefixRange>().is_err());
+        let ipv6: RoaPrefixRange = "::/0".parse()?;
+        assert_eq!((ipv6.prefix_length(), ipv6.max_length()), (0, None));
+        assert_eq!(
+            "::/0-128".parse::<RoaPrefixRange>()?.max_length(),
+            Some(128)
+        );
+        assert!("::/0-129".parse::<RoaPrefixRange>().is_err());
+
+        let ranges = RoaPrefixRanges::from_text(
+            [
+                "::/0-48",
+                "10.0.0.0/8",
+                "0.0.0.0/1",
+                "0.0.0.0/0-24",
+                "::/0",
+                "0.0.0.0/0-0",
+            ]
+            .map(Ok::<_, std::io::Error>),
+        )?;
+        assert_eq!(
+            ranges.to_text_string(),
+            "0.0.0.0/0\n0.0.0.0/0-24\n0.0.0.0/1\n10.0.0.0/8\n::/0\n::/0-48\n"
+        );
+        assert!(ranges.validate().contains(&Issue::RedundantMaxLength {
+            index: 5,
+            line: 6,
+            item: redundant
+        }));
+        Ok(())
+    }
+
+    #[test]
+    fn default_routes_econtent() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text(
+            ["0.0.0.0/0", "0.0.0.0/0-24", "::/0", "::/0-48"].map(Ok::<_, std::io::Error>),
+        )?;
+        let econtent: RouteOriginAttestation =
+            rasn::der::decode(&ranges.to_econtent(65000)?).context("failed to decode eContent")?;
+        let items = econtent
+            .ip_addr_blocks()
+            .flat_map(family_ranges)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(items, ranges.iter().copied().collect::<Vec<_>>());
+        Ok(())
+    }
+
+    #[test]
+    fn econtent_round_trip() -> anyhow::Result<()> {
+        let roa = include_bytes!("../tests/data/ok.roa");
+        let econtent = RoaPrefixRanges::from_roa(roa)?.to_econtent(65000)?;
+        // the original eContent is embedded verbatim in the ROA
+        assert!(roa.windows(econtent.len()).any(|window| window == econtent));
+        let ranges = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8-8")])?;
+        assert_eq!(
+            ranges.to_econtent(65000)?,
+            [
+                0x30, 0x15, 0x02, 0x03, 0x00, 0xfd, 0xe8, 0x30, 0x0e, 0x30, 0x0c, 0x04, 0x02, 0x00,
+                0x01, 0x30, 0x06, 0x30, 0x04, 0x03, 0x02, 0x00, 0x0a,
+            ]
+        );
+        assert!(std::iter::empty()
+            .collect::<RoaPrefixRanges>()
+            .to_econtent(65000)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn read_from_text() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/24"),
+            Ok("10.0.0.0/24-24"),
+            Ok("10.0.0.0/8"),
+            Ok("2001:db8:db8::/48"),
+            Ok("2001:db8::/32"),
+        ];
+        let expect = vec![
+            "10.0.0.0/8",
+            "10.0.0.0/24",
+            "2001:db8::/32",
+            "2001:db8:db8::/48",
+        ];
+        let mut errs = 0usize;
+        let output: Vec<_> = RoaPrefixRanges::from_text(input)?
+            .into_iter()
+            .enumerate()
+            .map(|(i, (item, j))| {
+                if i != j {
+                    errs += 1;
+                };
+                if item.has_explicit_equal_max_length() {
+                    errs += 1;
+                }
+                item.to_string()
+            })
+            .collect();
+        assert_eq!(output, expect);
+        assert_eq!(errs, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn host_bits_rejected() {
+        for (input, network) in [
+            ("10.0.0.5/8", "10.0.0.0/8"),
+            ("10.0.0.5/8-16", "10.0.0.0/8"),
+            ("192.0.2.129/25", "192.0.2.128/25"),
+            ("2001:db8::1/32", "2001:db8::/32"),
+            ("2001:db8:1::/32-48", "2001:db8::/32"),
+            ("[2001:db8::1]/64", "2001:db8::/64"),
+        ] {
+            let err = input.parse::<RoaPrefixRange>().unwrap_err();
+            assert!(
+                err.to_string()
+                    .contains(&format!("(did you mean {network}?)")),
+                "{input}: {err}"
+            );
+        }
+        #[cfg(feature = "json")]
+        assert!(RoaPrefixRanges::from_json(br#"[{"prefix": "10.0.0.5/8"}]"#).is_err());
+        assert!(
+            RoaPrefixRanges::from_rpsl([Ok::<_, std::io::Error>("route: 10.0.0.5/8")]).is_err()
+        );
+    }
+
+    #[test]
+    fn host_bits_masked() -> anyhow::Result<()> {
+        let mask = HostBits::Mask(|_, _| {});
+        for (input, expect) in [
+            ("10.0.0.5/8-16", "10.0.0.0/8-16"),
+            ("2001:db8::1/32+16", "2001:db8::/32-48"),
+            ("0.0.0.1/0", "0.0.0.0/0"),
+            ("[2001:db8::1]/64", "2001:db8::/64"),
+            ("192.0.2.1/32", "192.0.2.1/32"),
+        ] {
+            let item = RoaPrefixRange::from_str_with(input, mask)?;
+            assert_eq!(item.to_string(), expect, "{input}");
+        }
+        assert!(RoaPrefixRange::from_str_with("10.0.0.5/33", mask).is_err());
+        let expect: RoaPrefixRange = "10.0.0.0/8".parse()?;
+        #[cfg(feature = "json")]
+        {
+            let json = RoaPrefixRanges::from_json_with(br#"[{"prefix": "10.0.0.5/8"}]"#, mask)?;
+            assert!(json.iter().eq([&expect]));
+        }
+        let rpsl =
+            RoaPrefixRanges::from_rpsl_with([Ok::<_, std::io::Error>("route: 10.0.0.5/8")], mask)?;
+        assert!(rpsl.iter().eq([&expect]));
+        let csv = RoaPrefixRanges::from_vrp_csv_with(
+            [Ok::<_, std::io::Error>("AS65000,10.0.0.5/8,8")],
+            mask,
+        )?;
+        assert!(csv.iter().eq([&expect]));
+        let asn_text = parse_asn_text_with([Ok::<_, std::io::Error>("AS65000 10.0.0.5/8")], mask)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(asn_text, [(65000, expect)]);
+        Ok(())
+    }
+
+    #[test]
+    fn max_length_at_afi_maximum() -> anyhow::Result<()> {
+        for (host, prefix, max) in [
+            ("192.0.2.1/32-32", "192.0.2.0/24-32", 32),
+            ("2001:db8::1/128-128", "2001:db8::/32-128", 128),
+        ] {
+            let item: RoaPrefixRange = host.parse()?;
+            assert!(item.has_explicit_equal_max_length());
+            assert_eq!(item.max_length(), None);
+            assert_eq!(
+                format!("{item}"),
+                host.split('-').next().unwrap_or_default()
+            );
+            let ranges = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>(host)])?;
+            assert!(matches!(
+                ranges.validate()[..],
+                [Issue::RedundantMaxLength { index: 0, .. }]
+            ));
+
+            let item: RoaPrefixRange = prefix.parse()?;
+            assert!(!item.has_explicit_equal_max_length());
+            assert_eq!(item.max_length(), Some(max));
+            assert!(item.has_open_max_length());
+            assert_eq!(format!("{item}"), prefix);
+            assert!(
+                RoaPrefixRanges::from_text([Ok::<_, std::io::Error>(prefix)])?
+                    .validate()
+                    .is_empty()
+            );
+
+            assert!(prefix
+                .replace(&max.to_string(), &(max + 1).to_string())
+                .parse::<RoaPrefixRange>()
+                .is_err());
+        }
+
+        // decoded primitive lengths: an equal max_length is taken as implicit
+        let ipv4 = InnerRoaPrefixRange::<Ipv4>::from_primitives(&[192, 0, 2, 1], 32, 32)?;
+        assert_eq!(format!("{ipv4:#}"), "192.0.2.1/32");
+        let ipv4 =
+            InnerRoaPrefixRange::new(ipv4.prefix(), Some(PrefixLength::from_primitive(32)?))?;
+        assert!(RoaPrefixRange::Ipv4(ipv4).has_explicit_equal_max_length());
+        let mut octets = [0; 16];
+        octets[..4].copy_from_slice(&[0x20, 0x01, 0x0d, 0xb8]);
+        let ipv6 = InnerRoaPrefixRange::<Ipv6>::from_primitives(&octets, 32, 128)?;
+        assert_eq!(ipv6.to_string(), "2001:db8::/32-128");
+        let ipv6 =
+            InnerRoaPrefixRange::new(ipv6.prefix(), Some(PrefixLength::from_primitive(128)?))?;
+        assert_eq!(RoaPrefixRange::Ipv6(ipv6).max_length(), Some(128));
+        assert!(PrefixLength::<Ipv4>::from_primitive(33).is_err());
+        assert!(PrefixLength::<Ipv6>::from_primitive(129).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn max_length_delta() -> anyhow::Result<()> {
+        assert_eq!(
+            "10.0.0.0/8+2".parse::<RoaPrefixRange>()?,
+            "10.0.0.0/8-10".parse::<RoaPrefixRange>()?
+        );
+        assert_eq!(
+            "2001:db8::/32+16".parse::<RoaPrefixRange>()?.to_string(),
+            "2001:db8::/32-48"
+        );
+        assert!("10.0.0.0/24+9".parse::<RoaPrefixRange>().is_err());
+        assert!("2001:db8::/32+97".parse::<RoaPrefixRange>().is_err());
+        assert!("10.0.0.0/8+0".parse::<RoaPrefixRange>().is_err());
+        assert!("10.0.0.0/8+-1".parse::<RoaPrefixRange>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn validate() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/24"),
+            Ok("10.0.0.0/24-24"),
+            Ok("2001:db8::/32"),
+        ];
+        let issues: Vec<_> = RoaPrefixRanges::from_text(input)?
+            .validate()
+            .iter()
+            .map(|issue| (issue.index(), issue.kind()))
+            .collect();
+        let expect = vec![
+            (2, "misordered"),
+            (3, "redundant-maxlength"),
+            (3, "duplicate"),
+            (4, "duplicate"),
+        ];
+        assert_eq!(issues, expect);
+        Ok(())
+    }
+
+    #[test]
+    fn from_ranges() -> anyhow::Result<()> {
+        let items: Vec<RoaPrefixRange> = [
+            "2001:db8::/32",
+            "10.0.0.0/8-8",
+            "10.0.0.0/8",
+            "10.0.0.0/8-16",
+        ]
+        .iter()
+        .map(|item| item.parse())
+        .collect::<anyhow::Result<_>>()?;
+        let ranges = RoaPrefixRanges::from_ranges(items);
+        assert_eq!(ranges.stats().duplicates_removed, 1);
+        let canonical = ranges.canonical();
+        assert_eq!(
+            canonical
+                .iter()
+                .map(|item| format!("{item:#}"))
+                .collect::<Vec<_>>(),
+            vec!["10.0.0.0/8", "10.0.0.0/8-16", "2001:db8::/32"]
+        );
+        assert!(RoaPrefixRanges::from_ranges(canonical)
+            .validate()
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn iter_and_len() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8"),
+        ])?;
+        assert_eq!(ranges.len(), 2);
+        assert!(!ranges.is_empty());
+        assert_eq!(
+            ranges.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["10.0.0.0/8", "2001:db8::/32"]
+        );
+        assert_eq!(ranges.iter().next_back(), ranges.canonical().last());
+        assert!(RoaPrefixRanges::from_ranges([]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn retain_afi() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.1.0.0/16"),
+            Ok("2001:db8::/32"),
+            Ok("2001:db8::/48"),
+            Ok("10.1.0.0/16"),
+        ];
+        let mut ranges = RoaPrefixRanges::from_text(input)?;
+        ranges.retain_afi(concrete::Afi::Ipv4);
+        assert_eq!(ranges.to_text_string(), "10.0.0.0/8\n10.1.0.0/16\n");
+        let issues: Vec<_> = ranges
+            .validate()
+            .iter()
+            .map(|issue| (issue.index(), issue.kind()))
+            .collect();
+        assert_eq!(issues, vec![(5, "duplicate")]);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize_issue() -> anyhow::Result<()> {
+        let issue = Issue::RedundantMaxLength {
+            index: 2,
+            line: 3,
+            item: "10.0.0.0/24-24".parse()?,
+        };
+        assert_eq!(
+            serde_json::to_string(&issue)?,
+            r#"{"line":3,"kind":"redundant-maxlength","value":"10.0.0.0/24-24"}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_canonical() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("2001:db8::/32"),
+        ];
+        assert!(RoaPrefixRanges::from_text(input)?.validate().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn first_difference() -> anyhow::Result<()> {
+        let canonical = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("2001:db8::/32"),
+        ])?;
+        assert_eq!(canonical.first_difference(), None);
+        let misordered = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/8-16"),
+            Ok("10.0.0.0/16"),
+        ])?;
+        assert_eq!(
+            misordered.first_difference().map(|diff| diff.to_string()),
+            Some("line 2: found 2001:db8::/32, expected 10.0.0.0/8-16".to_string())
+        );
+        let trailing_duplicate =
+            RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8"), Ok("10.0.0.0/8")])?;
+        assert_eq!(
+            trailing_duplicate
+                .first_difference()
+                .map(|diff| diff.to_string()),
+            Some("line 2: found 10.0.0.0/8, expected end of input".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fold_max_length() -> anyhow::Result<()> {
+        let mut ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-12"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("10.0.0.0/16"),
+            Ok("2001:db8::/32"),
+        ])?;
+        let folded: Vec<_> = ranges
+            .fold_max_length()
+            .into_iter()
+            .map(|(item, kept)| format!("{item} -> {kept}"))
+            .collect();
+        assert_eq!(
+            folded,
+            vec![
+                "10.0.0.0/8 -> 10.0.0.0/8-16",
+                "10.0.0.0/8-12 -> 10.0.0.0/8-16"
+            ]
+        );
+        let output: Vec<_> = ranges
+            .into_iter()
+            .map(|(item, _)| item.to_string())
+            .collect();
+        assert_eq!(
+            output,
+            vec!["10.0.0.0/8-16", "10.0.0.0/16", "2001:db8::/32"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn to_text_string() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32-32"),
+            Ok("10.0.0.0/8-16"),
+            Ok("10.0.0.0/8"),
+        ])?;
+        assert_eq!(
+            ranges.to_text_string(),
+            "10.0.0.0/8\n10.0.0.0/8-16\n2001:db8::/32\n"
+        );
+        let empty: RoaPrefixRanges = std::iter::empty().collect();
+        assert_eq!(empty.to_text_string(), "");
+        Ok(())
+    }
+
+    #[test]
+    fn changes_from() -> anyhow::Result<()> {
+        let old = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/16-24"),
+            Ok("192.0.2.0/24"),
+            Ok("2001:db8::/32"),
+            Ok("2001:db8::/48"),
+            Ok("2001:db8::/48-56"),
+        ])?;
+        let new = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/16-20"),
+            Ok("192.0.2.0/24-24"),
+            Ok("198.51.100.0/24"),
+            Ok("2001:db8::/32-48"),
+            Ok("2001:db8::/48-64"),
+        ])?;
+        assert_eq!(
+            new.changes_from(&old),
+            vec![
+                Change::MaxLengthChanged {
+                    prefix: "10.0.0.0/16".parse()?,
+                    from: 24,
+                    to: 20,
+                },
+                Change::Added("198.51.100.0/24".parse()?),
+                Change::MaxLengthChanged {
+                    prefix: "2001:db8::/32".parse()?,
+                    from: 32,
+                    to: 48,
+                },
+                Change::Removed("2001:db8::/48".parse()?),
+                Change::Removed("2001:db8::/48-56".parse()?),
+                Change::Added("2001:db8::/48-64".parse()?),
+            ]
+        );
+        assert!(new.changes_from(&new).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn covers() -> anyhow::Result<()> {
+        let parent: RoaPrefixRange = "10.0.0.0/8-16".parse()?;
+        assert!(parent.covers(&parent));
+        assert!(parent.covers(&"10.0.0.0/8".parse()?));
+        assert!(parent.covers(&"10.1.0.0/16".parse()?));
+        assert!(parent.covers(&"10.0.0.0/12-16".parse()?));
+        assert!(!parent.covers(&"10.0.0.0/8-24".parse()?));
+        assert!(!parent.covers(&"10.1.0.0/24".parse()?));
+        assert!(!parent.covers(&"11.0.0.0/16".parse()?));
+        assert!(!parent.covers(&"::/8".parse()?));
+        Ok(())
+    }
+
+    #[test]
+    fn symmetric_difference() -> anyhow::Result<()> {
+        let old = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("192.0.2.0/24-24"),
+        ])?;
+        let new = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-16"),
+            Ok("192.0.2.0/24"),
+            Ok("2001:db8::/32-48"),
+            Ok("2001:db8::/48"),
+        ])?;
+        let render = |items: Vec<RoaPrefixRange>| -> Vec<_> {
+            items.iter().map(ToString::to_string).collect()
+        };
+        let (removed, added) = old.symmetric_difference(&new);
+        assert_eq!(render(removed), ["10.0.0.0/8", "2001:db8::/32"]);
+        assert_eq!(render(added), ["2001:db8::/32-48", "2001:db8::/48"]);
+        let (removed, added) = new.symmetric_difference(&old);
+        assert_eq!(render(added), ["10.0.0.0/8", "2001:db8::/32"]);
+        assert_eq!(render(removed), ["2001:db8::/32-48", "2001:db8::/48"]);
+        let (removed, added) = old.symmetric_difference(&old);
+        assert!(removed.is_empty() && added.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn difference() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("10.1.0.0/16"),
+            Ok("2001:db8::/32"),
+        ])?;
+        let excluded = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-16"),
+            Ok("192.0.2.0/24"),
+        ])?;
+        let render = |ranges: RoaPrefixRanges| -> Vec<_> {
+            ranges
+                .into_iter()
+                .map(|(item, _)| item.to_string())
+                .collect()
+        };
+        assert_eq!(
+            render(ranges.difference(&excluded)),
+            vec!["10.0.0.0/8", "10.1.0.0/16", "2001:db8::/32"]
+        );
+        assert_eq!(
+            render(ranges.uncovered_by(&excluded)),
+            vec!["2001:db8::/32"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_form() -> anyhow::Result<()> {
+        let input = [
+            Ok::<_, std::io::Error>("9.0.0.0/8"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+        ];
+        let ranges = RoaPrefixRanges::from_text(input)?;
+        assert!(ranges.validate_in(CanonicalForm::Draft).is_empty());
+        assert_eq!(
+            ranges.validate_in(CanonicalForm::Legacy),
+            vec![Issue::Misordered {
+                index: 1,
+                line: 2,
+                item: "10.0.0.0/8".parse()?
+            }]
+        );
+        let mut items: Vec<_> = ranges.into_iter().map(|(item, _)| item).collect();
+        items.sort_by(|a, b| CanonicalForm::Legacy.compare(a, b));
+        let output: Vec<_> = items.iter().map(ToString::to_string).collect();
+        assert_eq!(output, vec!["10.0.0.0/8", "10.0.0.0/8-16", "9.0.0.0/8"]);
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_in_place() -> anyhow::Result<()> {
+        let mut ranges =
+            RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8"), Ok("10.0.0.0/16")])?;
+        ranges.extend(
+            ["2001:db8::/32-32", "10.0.0.0/12", "10.0.0.0/8"]
+                .into_iter()
+                .map(str::parse)
+                .collect::<anyhow::Result<Vec<RoaPrefixRange>>>()?,
+        );
+        assert_eq!(ranges.validate().len(), 3);
+        ranges.canonicalize_in_place();
+        assert!(ranges.validate().is_empty());
+        ranges.canonicalize_in_place();
+        assert!(ranges.validate().is_empty());
+        let output: Vec<_> = ranges
+            .into_iter()
+            .map(|(item, _)| format!("{item:#}"))
+            .collect();
+        let expect = vec!["10.0.0.0/8", "10.0.0.0/12", "10.0.0.0/16", "2001:db8::/32"];
+        assert_eq!(output, expect);
+        Ok(())
+    }
+
+    #[test]
+    fn aggregation_candidates() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/10"),
+            Ok("10.64.0.0/10"),
+            Ok("10.128.0.0/10"),
+            Ok("10.192.0.0/10"),
+            Ok("192.0.2.0/25-26"),
+            Ok("192.0.2.128/25"),
+            Ok("2001:db8::/33-48"),
+            Ok("2001:db8:8000::/33-48"),
+        ];
+        let output: Vec<_> = RoaPrefixRanges::from_text(input)?
+            .aggregation_candidates()?
+            .into_iter()
+            .map(|(item, count)| (item.to_string(), count))
+            .collect();
+        let expect = vec![
+            ("10.0.0.0/8-10".to_string(), 4),
+            ("2001:db8::/32-48".to_string(), 2),
+        ];
+        assert_eq!(output, expect);
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_sorted_input() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8-8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("2001:db8::/32"),
+        ];
+        let output: Vec<_> = dedup_sorted(parse_numbered_text(input))
+            .map(|item| item.map(|item| item.to_string()))
+            .collect::<anyhow::Result<_>>()?;
+        assert_eq!(output, vec!["10.0.0.0/8", "10.0.0.0/8-16", "2001:db8::/32"]);
+        Ok(())
+    }
+
+    #[test]
+    fn dedup_unsorted_input() {
+        let input = vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/16"),
+        ];
+        let err = dedup_sorted(parse_numbered_text(input))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 3:"));
+    }
+
+    #[test]
+    fn line_numbers_count_comments() -> anyhow::Result<()> {
+        let input = [
+            "# ROA requests",
+            "",
+            "10.0.0.0/8",
+            "2001:db8::/32  # documentation",
+            "10.0.0.0/16",
+        ];
+        let lines = || input.map(Ok::<_, std::io::Error>);
+        let ranges = RoaPrefixRanges::from_text(lines())?;
+        assert_eq!(
+            ranges.validate(),
+            [Issue::Misordered {
+                index: 2,
+                line: 5,
+                item: "10.0.0.0/16".parse()?
+            }]
+        );
+        assert_eq!(
+            ranges.validate()[0].to_string(),
+            "line 5: item 10.0.0.0/16 is mis-ordered"
+        );
+        assert_eq!(
+            ranges.first_difference().map(|diff| diff.to_string()),
+            Some("line 4: found 2001:db8::/32, expected 10.0.0.0/16".to_string())
+        );
+        let err = dedup_sorted(parse_numbered_text(lines()))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .unwrap_err();
+        assert!(err.to_string().starts_with("line 5:"));
+        let issues = super::stream_issues(parse_numbered_text(lines()), CanonicalForm::Draft)
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(issues, ranges.validate());
+        let rpsl = RoaPrefixRanges::from_rpsl(
+            [
+                "% whois",
+                "route: 192.0.2.0/24",
+                "origin: AS65000",
+                "route: 10.0.0.0/8",
+            ]
+            .map(Ok::<_, std::io::Error>),
+        )?;
+        assert_eq!(rpsl.validate()[0].line(), 4);
+        let csv = RoaPrefixRanges::from_vrp_csv(
+            [
+                "ASN,IP Prefix,Max Length",
+                "AS65000,192.0.2.0/24,24",
+                "AS65000,10.0.0.0/8,8",
+            ]
+            .map(Ok::<_, std::io::Error>),
+        )?;
+        assert_eq!(csv.validate()[0].line(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn is_sorted_input() -> anyhow::Result<()> {
+        let sorted: Vec<_> = parse_text(vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8-16"),
+            Ok("2001:db8::/32"),
+        ])
+        .collect::<anyhow::Result<_>>()?;
+        assert_eq!(is_sorted(sorted), Ok(()));
+        let unsorted: Vec<_> = parse_text(vec![
+            Ok::<_, std::io::Error>("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("10.0.0.0/16"),
+            Ok("10.0.0.0/8"),
+        ])
+        .collect::<anyhow::Result<_>>()?;
+        assert_eq!(is_sorted(unsorted), Err(2));
+        let single: Vec<_> = parse_text(vec![Ok::<_, std::io::Error>("2001:db8::/32")])
+            .collect::<anyhow::Result<_>>()?;
+        assert_eq!(is_sorted(single), Ok(()));
+        assert_eq!(is_sorted(Vec::new()), Ok(()));
+        Ok(())
+    }
+
+    #[test]
+    fn trim_list_markers_from_line() {
+        assert_eq!(trim_list_markers("- 10.0.0.0/8-16"), "10.0.0.0/8-16");
+        assert_eq!(trim_list_markers("  * 10.0.0.0/8"), "10.0.0.0/8");
+        assert_eq!(
+            trim_list_markers("•\t2001:db8::/32-48,"),
+            "2001:db8::/32-48"
+        );
+        assert_eq!(trim_list_markers("10.0.0.0/8 ,"), "10.0.0.0/8");
+        assert_eq!(trim_list_markers("-10.0.0.0/8"), "-10.0.0.0/8");
+        assert_eq!(trim_list_markers("10.0.0.0/8-16"), "10.0.0.0/8-16");
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn from_json() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_json(
+            br#"[{"prefix": "2001:db8::/32"}, {"prefix": "10.0.0.0/8", "maxLength": 16}]"#,
+        )?;
+        assert_eq!(ranges.to_text_string(), "10.0.0.0/8-16\n2001:db8::/32\n");
+        assert_eq!(
+            serde_json::to_string(&ranges.into_iter().map(|(item, _)| item).collect::<Vec<_>>())?,
+            r#"[{"prefix":"10.0.0.0/8","maxLength":16},{"prefix":"2001:db8::/32"}]"#
+        );
+        let err = RoaPrefixRanges::from_json(br#"[{"prefix": "10.0.0.0/8", "maxLength": 7}]"#)
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("less than prefix length"));
+        assert!(RoaPrefixRanges::from_json(br#"{"prefix": "10.0.0.0/8"}"#).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn normalize_slashes_in_line() {
+        assert_eq!(normalize_slashes("10.0.0.0\u{ff0f}8-16"), "10.0.0.0/8-16");
+        assert_eq!(normalize_slashes("2001:db8::\u{2044}32"), "2001:db8::/32");
+        assert_eq!(normalize_slashes("10.0.0.0\u{2215}8"), "10.0.0.0/8");
+        assert!(matches!(normalize_slashes("10.0.0.0/8"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn binary_text_input_hint() {
+        let input = vec![Ok::<_, std::io::Error>("0\u{2}\u{1}\u{3}")];
+        let err = RoaPrefixRanges::from_text(input).unwrap_err();
+        assert_eq!(err.to_string(), BINARY_INPUT_HINT);
+        let input = vec![Err::<&str, _>(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        ))];
+        let err = RoaPrefixRanges::from_text(input).unwrap_err();
+        assert_eq!(err.to_string(), BINARY_INPUT_HINT);
+    }
+
+    #[test]
+    fn alternate_preserves_explicit_equal() -> anyhow::Result<()> {
+        let item = "10.0.0.0/8-8".parse::<RoaPrefixRange>()?;
+        assert_eq!(item.to_string(), "10.0.0.0/8");
+        assert_eq!(format!("{item:#}"), "10.0.0.0/8-8");
+        let item = "10.0.0.0/8".parse::<RoaPrefixRange>()?;
+        assert_eq!(format!("{item:#}"), "10.0.0.0/8");
+        Ok(())
+    }
+
+    #[test]
+    fn exceeds_expansion() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-24"),
+            Ok("10.0.0.0/16-24"),
+            Ok("10.0.0.0/24-24"),
+            Ok("192.0.2.0/24"),
+            Ok("2001:db8::/32-48"),
+            Ok("2001:db8::/32-49"),
+        ])?;
+        let flagged: Vec<_> = ranges
+            .exceeding_expansion(8)
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            flagged,
+            vec!["10.0.0.0/8-24", "2001:db8::/32-48", "2001:db8::/32-49"]
+        );
+        assert_eq!(ranges.exceeding_expansion(16).count(), 1);
+        assert_eq!(ranges.exceeding_expansion(0).count(), 4);
+        Ok(())
+    }
+
+    #[test]
+    fn more_specific_levels() -> anyhow::Result<()> {
+        assert_eq!(
+            "10.0.0.0/8"
+                .parse::<RoaPrefixRange>()?
+                .more_specific_levels(),
+            0
+        );
+        assert_eq!(
+            "10.0.0.0/8-8"
+                .parse::<RoaPrefixRange>()?
+                .more_specific_levels(),
+            0
+        );
+        assert_eq!(
+            "10.0.0.0/8-24"
+                .parse::<RoaPrefixRange>()?
+                .more_specific_levels(),
+            16
+        );
+        assert_eq!(
+            "::/0-128".parse::<RoaPrefixRange>()?.more_specific_levels(),
+            128
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn open_max_length() -> anyhow::Result<()> {
+        assert!("10.0.0.0/8-32"
+            .parse::<RoaPrefixRange>()?
+            .has_open_max_length());
+        assert!("2001:db8::/32-128"
+            .parse::<RoaPrefixRange>()?
+            .has_open_max_length());
+        assert!(!"10.0.0.0/8-31"
+            .parse::<RoaPrefixRange>()?
+            .has_open_max_length());
+        assert!(!"192.0.2.1/32-32"
+            .parse::<RoaPrefixRange>()?
+            .has_open_max_length());
+        assert!(!"192.0.2.0/24"
+            .parse::<RoaPrefixRange>()?
+            .has_open_max_length());
+        Ok(())
+    }
+
+    #[test]
+    fn ipv4_embedded() -> anyhow::Result<()> {
+        assert!("::ffff:192.0.2.0/120"
+            .parse::<RoaPrefixRange>()?
+            .is_ipv4_embedded());
+        assert!("::192.0.2.0/120"
+            .parse::<RoaPrefixRange>()?
+            .is_ipv4_embedded());
+        assert!(!"2001:db8::/32"
+            .parse::<RoaPrefixRange>()?
+            .is_ipv4_embedded());
+        assert!(!"::/0".parse::<RoaPrefixRange>()?.is_ipv4_embedded());
+        assert!(!"::/96".parse::<RoaPrefixRange>()?.is_ipv4_embedded());
+        assert!(!"::/128".parse::<RoaPrefixRange>()?.is_ipv4_embedded());
+        assert!(!"::1/128".parse::<RoaPrefixRange>()?.is_ipv4_embedded());
+        assert!(!"192.0.2.0/24".parse::<RoaPrefixRange>()?.is_ipv4_embedded());
+        Ok(())
+    }
+
+    #[test]
+    fn has_afi() -> anyhow::Result<()> {
+        let ipv4_only = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("10.0.0.0/8")])?;
+        assert!(ipv4_only.has_ipv4());
+        assert!(!ipv4_only.has_ipv6());
+        let ipv6_only = RoaPrefixRanges::from_text([Ok::<_, std::io::Error>("2001:db8::/32")])?;
+        assert!(!ipv6_only.has_ipv4());
+        assert!(ipv6_only.has_ipv6());
+        let mixed = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+        ])?;
+        assert!(mixed.has_ipv4());
+        assert!(mixed.has_ipv6());
+        Ok(())
+    }
+
+    #[test]
+    fn bracketed_address() -> anyhow::Result<()> {
+        let item: RoaPrefixRange = "[2001:db8::]/32".parse()?;
+        assert_eq!(item, "2001:db8::/32".parse()?);
+        let item: RoaPrefixRange = "[2001:db8::]/32-48".parse()?;
+        assert_eq!(item.to_string(), "2001:db8::/32-48");
+        assert!("[2001:db8::/32".parse::<RoaPrefixRange>().is_err());
+        assert!("[[2001:db8::]]/32".parse::<RoaPrefixRange>().is_err());
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serialize() -> anyhow::Result<()> {
+        let items: Vec<RoaPrefixRange> = vec![
+            "10.0.0.0/8".parse()?,
+            "10.0.0.0/8-8".parse()?,
+            "2001:db8::/32-48".parse()?,
+        ];
+        assert_eq!(
+            serde_json::to_string(&items)?,
+            r#"[{"prefix":"10.0.0.0/8"},{"prefix":"10.0.0.0/8"},{"prefix":"2001:db8::/32","maxLength":48}]"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn afi() -> anyhow::Result<()> {
+        let ipv4: RoaPrefixRange = "192.0.2.0/24-28".parse()?;
+        assert_eq!(ipv4.afi(), concrete::Afi::Ipv4);
+        let ipv6: RoaPrefixRange = "2001:db8::/32".parse()?;
+        assert_eq!(ipv6.afi(), concrete::Afi::Ipv6);
+        Ok(())
+    }
+
+    #[test]
+    fn stats() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+            Ok("10.0.0.0/8-8"),
+            Ok("10.0.0.0/24"),
+            Ok("2001:db8::/32"),
+            Ok("2001:db8::/48-64"),
+            Ok("10.1.0.0/16-16"),
+        ];
+        let stats = RoaPrefixRanges::from_text(input)?.stats();
+        assert_eq!(
+            stats,
+            Stats {
+                total: 5,
+                ipv4: 3,
+                ipv6: 2,
+                duplicates_removed: 2,
+                explicit_max_length: 2,
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "5 entries (3 IPv4, 2 IPv6), 2 duplicates removed, 2 with explicit max_length"
+        );
+        #[cfg(feature = "serde")]
+        assert_eq!(
+            serde_json::to_string(&stats)?,
+            r#"{"total":5,"ipv4":3,"ipv6":2,"duplicatesRemoved":2}"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn covered_count() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-16"),
+            Ok("10.1.0.0/16"),
+            Ok("10.0.0.0/8-12"),
+            Ok("10.0.0.0/8"),
+            Ok("2001:db8::/32"),
+            Ok("2001:db8::/48"),
+        ])?;
+        assert_eq!(ranges.covered_count(), 3);
+        Ok(())
+    }
+
+    fn aggregated(input: &[&str]) -> anyhow::Result<String> {
+        let mut ranges = RoaPrefixRanges::from_text(input.iter().map(Ok::<_, std::io::Error>))?;
+        ranges.aggregate()?;
+        Ok(ranges.to_text_string())
+    }
+
+    #[test]
+    fn aggregate_covered() -> anyhow::Result<()> {
+        assert_eq!(
+            aggregated(&["10.0.0.0/24", "10.0.0.0/8-24", "10.1.0.0/16", "10.0.0.0/8"])?,
+            "10.0.0.0/8-24\n"
+        );
+        assert_eq!(
+            aggregated(&["10.0.0.0/24", "10.0.0.0/24-26", "10.0.0.0/25-26"])?,
+            "10.0.0.0/24-26\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_children() -> anyhow::Result<()> {
+        assert_eq!(
+            aggregated(&["10.0.0.0/24", "10.0.0.0/25", "10.0.0.128/25"])?,
+            "10.0.0.0/24-25\n"
+        );
+        assert_eq!(
+            aggregated(&["2001:db8::/32", "2001:db8::/33-48", "2001:db8:8000::/33-48"])?,
+            "2001:db8::/32-48\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_repeatedly() -> anyhow::Result<()> {
+        assert_eq!(
+            aggregated(&[
+                "10.0.0.0/23",
+                "10.0.0.0/24",
+                "10.0.0.0/25",
+                "10.0.0.128/25",
+                "10.0.1.0/24-25",
+                "10.0.1.0/26",
+            ])?,
+            "10.0.0.0/23-25\n10.0.1.0/26\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_not_mergeable() -> anyhow::Result<()> {
+        for input in [
+            // siblings without their parent
+            &["10.0.0.0/25", "10.0.0.128/25"][..],
+            // a child missing
+            &["10.0.0.0/24", "10.0.0.0/25"],
+            // children with different max_length
+            &["10.0.0.0/24", "10.0.0.0/25", "10.0.0.128/25-26"],
+            // a gap in prefix lengths
+            &["10.0.0.0/24", "10.0.0.0/26", "10.0.0.64/26"],
+            // different address families
+            &["0.0.0.0/0-32", "::/0"],
+            // host routes
+            &["192.0.2.1/32", "2001:db8::1/128"],
+        ] {
+            let mut expect = input.to_vec();
+            expect.sort_by_key(|item| item.parse::<RoaPrefixRange>().ok());
+            assert_eq!(
+                aggregated(input)?,
+                expect
+                    .iter()
+                    .map(|item| format!("{item}\n"))
+                    .collect::<String>()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn aggregate_per_asn() -> anyhow::Result<()> {
+        let input = [
+            "AS65000 10.0.0.0/8-24",
+            "AS65001 10.0.0.0/16",
+            "AS65000 10.1.0.0/16",
+        ];
+        let mut groups = super::group_by_asn(super::parse_asn_text(
+            input.iter().map(Ok::<_, std::io::Error>),
+        ))?;
+        for ranges in groups.values_mut() {
+            ranges.aggregate()?;
+        }
+        assert_eq!(groups[&65000].to_text_string(), "10.0.0.0/8-24\n");
+        assert_eq!(groups[&65001].to_text_string(), "10.0.0.0/16\n");
+        assert_eq!(groups[&65000].as_id(), Some(65000));
+        Ok(())
+    }
+
+    #[test]
+    fn stream_issues() -> anyhow::Result<()> {
+        let input = [
+            "10.0.0.0/8",
+            "10.0.0.0/8",
+            "10.0.0.0/16-16",
+            "192.0.2.0/24",
+            "10.1.0.0/16",
+        ];
+        let issues = super::stream_issues(
+            parse_numbered_text(input.map(Ok::<_, std::io::Error>)),
+            CanonicalForm::Draft,
+        )
+        .collect::<anyhow::Result<Vec<_>>>()?;
+        assert_eq!(
+            issues
+                .iter()
+                .map(|issue| (issue.index(), issue.kind()))
+                .collect::<Vec<_>>(),
+            vec![
+                (1, "duplicate"),
+                (2, "redundant-maxlength"),
+                (4, "misordered"),
+            ]
+        );
+        let ranges = RoaPrefixRanges::from_text(input.map(Ok::<_, std::io::Error>))?;
+        assert_eq!(ranges.validate_in(CanonicalForm::Draft), issues);
+        Ok(())
+    }
+
+    #[test]
+    fn overlaps() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-16"),
+            Ok("10.1.0.0/16"),
+            Ok("10.1.0.0/24"),
+            Ok("10.0.0.0/8"),
+            Ok("11.0.0.0/16"),
+            Ok("2001:db8::/32-48"),
+            Ok("2001:db8::/48"),
+            Ok("2001:db8::/64"),
+        ])?;
+        let pairs: Vec<_> = ranges
+            .overlaps()
+            .iter()
+            .map(|(covering, covered)| format!("{covering} > {covered}"))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                "10.0.0.0/8-16 > 10.0.0.0/8",
+                "10.0.0.0/8-16 > 10.1.0.0/16",
+                "2001:db8::/32-48 > 2001:db8::/48",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn prefix_length_histogram() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("10.0.0.0/8-16"),
+            Ok("10.1.0.0/16"),
+            Ok("192.0.2.0/24"),
+            Ok("198.51.100.0/24"),
+            Ok("2001:db8::/32"),
+        ])?;
+        assert_eq!(
+            ranges.prefix_length_histogram(concrete::Afi::Ipv4),
+            BTreeMap::from([(8, 1), (16, 1), (24, 2)])
+        );
+        assert_eq!(
+            ranges.prefix_length_histogram(concrete::Afi::Ipv6),
+            BTreeMap::from([(32, 1)])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn per_afi_ranges() -> anyhow::Result<()> {
+        let ranges = RoaPrefixRanges::from_text([
+            Ok::<_, std::io::Error>("2001:db8::/32-48"),
+            Ok("10.0.0.0/8"),
+            Ok("192.0.2.0/24-28"),
+        ])?;
+        let ipv4: Vec<_> = ranges
+            .ipv4_ranges()
+            .map(|inner| {
+                (
+                    inner.prefix().to_string(),
+                    inner.max_length().into_primitive(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            ipv4,
+            vec![
+                ("10.0.0.0/8".to_string(), 8),
+                ("192.0.2.0/24".to_string(), 28)
+            ]
+        );
+        let ipv6: Vec<_> = ranges
+            .ipv6_ranges()
+            .map(|inner| {
+                (
+                    inner.prefix().to_string(),
+                    inner.max_length().into_primitive(),
+                )
+            })
+            .collect();
+        assert_eq!(ipv6, vec![("2001:db8::/32".to_string(), 48)]);
+        Ok(())
+    }
+
+    #[test]
+    fn afi_summary() -> anyhow::Result<()> {
+        let input = vec![
+            Ok::<_, std::io::Error>("2001:db8::/32"),
+            Ok("10.0.0.0/8"),
+            Ok("2001:db8:db8::/48"),
+            Ok("10.0.0.0/24"),
+            Ok("192.0.2.0/24"),
+        ];
+        let output: Vec<_> = RoaPrefixRanges::from_text(input)?
+            .afi_summary()
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        let expect = vec![
+            "ipv4: 3 entries, first 10.0.0.0/8, last 192.0.2.0/24",
+            "ipv6: 2 entries, first 2001:db8::/32, last 2001:db8:db8::/48",
+        ];
+        assert_eq!(output, expect);
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn as_text_round_trip() -> anyhow::Result<()> {
+        #[derive(Serialize, Deserialize)]
+        struct Wrapper(#[serde(with = "as_text")] RoaPrefixRange);
+
+        for input in [
+            "10.0.0.0/8",
+            "10.0.0.0/8-8",
+            "10.0.0.0/8-24",
+            "2001:db8::/32-48",
+        ] {
+            let json = serde_json::to_string(&Wrapper(input.parse()?))?;
+            assert_eq!(json, format!("\"{input}\""));
+            let Wrapper(range) = serde_json::from_str(&json)?;
+            assert_eq!(format!("{range:#}"), input);
+        }
+        assert!(serde_json::from_str::<Wrapper>("\"10.0.0.0/8-7\"").is_err());
+        assert!(serde_json::from_str::<Wrapper>(r#"{"prefix": "10.0.0.0/8"}"#).is_err());
         Ok(())
     }
 
@@ -1,4 +1,15 @@
 //! Support library for `roasort`.
+//!
+//! Sorts and de-duplicates ROA IP address information according to the
+//! canonicalization process described in `draft-ietf-sidrops-rfc6482bis`.
+//!
+//! ```
+//! let lines = ["2001:db8::/32", "192.0.2.0/24-24", "192.0.2.0/24"];
+//! let sorted = roasort::sort_text(lines.map(Ok::<_, std::io::Error>))?;
+//! let text: Vec<_> = sorted.iter().map(ToString::to_string).collect();
+//! assert_eq!(text, ["192.0.2.0/24", "2001:db8::/32"]);
+//! # Ok::<_, anyhow::Error>(())
+//! ```
 #![doc(html_root_url = "https://docs.rs/roasort/0.1.0")]
 // clippy lints
 #![warn(clippy::pedantic)]
@@ -45,14 +56,28 @@
 
 mod cli;
 mod econtent;
+#[cfg(feature = "rpki-interop")]
+mod interop;
 mod ir;
+mod roa;
 
 pub use cli::main;
+#[cfg(feature = "serde")]
+pub use ir::as_text;
+pub use ir::{
+    check_roa_order, dedup_sorted, group_by_asn, is_sorted, parse_asn_text, parse_asn_text_with,
+    parse_numbered_text, parse_numbered_text_with, parse_text, parse_text_with, parse_vrp_csv,
+    parse_vrp_csv_with, sort_roa, sort_text, stream_issues, AfiSummary, CanonicalForm, Change,
+    DiffLine, FirstDifference, HostBits, InnerRoaPrefixRange, Issue, RoaOrderIssue, RoaPrefixRange,
+    RoaPrefixRanges, Stats,
+};
+pub use roa::Roa;
 
 // silence unused dev-dependency warnings
 #[cfg(test)]
 mod deps {
     use assert_cmd as _;
     use predicates as _;
+    use serde_json as _;
     use version_sync as _;
 }
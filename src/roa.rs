@@ -0,0 +1,384 @@
+use std::borrow::Cow;
+
+use anyhow::Context;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use chrono::{DateTime, FixedOffset};
+
+use ip::{any, concrete, Ipv4, Ipv6, PrefixLength};
+
+use rasn::{
+    der,
+    types::{Any, GeneralizedTime, OctetString, Oid, UtcTime},
+    AsnType, Decode,
+};
+
+use rasn_cms::{SignedData, SignerInfo, SignerInfos, CONTENT_SIGNED_DATA};
+
+use sha2::{Digest, Sha256};
+
+use crate::econtent::{RoaContentInfo, RouteOriginAttestation, ID_CT_ROUTE_ORIGIN_AUTHZ};
+
+const ID_SHA256: &Oid = Oid::const_new(&[2, 16, 840, 1, 101, 3, 4, 2, 1]);
+
+const ID_MESSAGE_DIGEST: &Oid = Oid::const_new(&[1, 2, 840, 113_549, 1, 9, 4]);
+
+const ID_SIGNING_TIME: &Oid = Oid::const_new(&[1, 2, 840, 113_549, 1, 9, 5]);
+
+/// The value of a signing-time attribute.
+#[derive(Debug, Clone, AsnType, Decode)]
+#[rasn(choice)]
+enum Time {
+    Utc(UtcTime),
+    General(GeneralizedTime),
+}
+
+/// A decoded ROA, giving structured access to its eContent.
+#[derive(Debug, Clone)]
+pub struct Roa {
+    econtent: RouteOriginAttestation,
+    econtent_der: OctetString,
+    signer_infos: SignerInfos,
+}
+
+impl Roa {
+    /// Decode a single DER-encoded ROA.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `bytes` is not a DER-encoded ROA with embedded
+    /// eContent of a supported version.
+    pub fn from_der(bytes: &[u8]) -> anyhow::Result<Self> {
+        log::info!("trying to decode ROA from input bytes");
+        der::decode::<RoaContentInfo>(bytes)
+            .context("failed to decode ContentInfo")?
+            .try_into()
+    }
+
+    /// Decode one or more DER-encoded ROAs, concatenated back to back.
+    pub(crate) fn from_der_concatenated(mut bytes: &[u8]) -> anyhow::Result<Vec<Self>> {
+        let total = bytes.len();
+        let mut roas = Vec::new();
+        while !bytes.is_empty() {
+            let offset = total - bytes.len();
+            let (der, rest) = split_der(bytes).with_context(|| {
+                if offset == 0 {
+                    "input is not a DER-encoded ROA".to_string()
+                } else {
+                    format!("trailing data at offset {offset} is not a DER-encoded ROA")
+                }
+            })?;
+            roas.push(
+                Self::from_der(der)
+                    .with_context(|| format!("failed to decode ROA at offset {offset}"))?,
+            );
+            bytes = rest;
+        }
+        anyhow::ensure!(!roas.is_empty(), "input is empty");
+        Ok(roas)
+    }
+
+    pub(crate) const fn econtent(&self) -> &RouteOriginAttestation {
+        &self.econtent
+    }
+
+    /// Check that the message digest in the signed attributes of the ROA's
+    /// signer matches the eContent.
+    ///
+    /// This does not verify the signature over the signed attributes, nor
+    /// the EE certificate that it is made with.
+    pub(crate) fn verify_digest(&self) -> anyhow::Result<()> {
+        log::info!("checking the signed message digest of the eContent");
+        let algorithm = &self.signer_info()?.digest_algorithm.algorithm;
+        if ID_SHA256 != *algorithm {
+            anyhow::bail!("unsupported digest algorithm {algorithm:?}");
+        }
+        let message_digest = self
+            .signed_attribute(ID_MESSAGE_DIGEST)?
+            .context("ROA signer has no message-digest signed attribute")?;
+        let message_digest: OctetString =
+            der::decode(message_digest.as_bytes()).context("failed to decode message-digest")?;
+        if Sha256::digest(&self.econtent_der).as_slice() != message_digest.as_ref() {
+            anyhow::bail!("signed message digest does not match the ROA eContent");
+        }
+        Ok(())
+    }
+
+    /// The signing time, from the signed attributes of the ROA's signer, if
+    /// present.
+    pub(crate) fn signing_time(&self) -> anyhow::Result<Option<DateTime<FixedOffset>>> {
+        self.signed_attribute(ID_SIGNING_TIME)?
+            .map(|value| {
+                der::decode::<Time>(value.as_bytes())
+                    .context("failed to decode signing-time")
+                    .map(|time| match time {
+                        Time::Utc(time) => time.into(),
+                        Time::General(time) => time,
+                    })
+            })
+            .transpose()
+    }
+
+    /// The only `SignerInfo`, as required for a ROA.
+    fn signer_info(&self) -> anyhow::Result<&SignerInfo> {
+        let mut signer_infos = self.signer_infos.iter();
+        match (signer_infos.next(), signer_infos.next()) {
+            (Some(signer_info), None) => Ok(signer_info),
+            _ => anyhow::bail!(
+                "expected exactly one SignerInfo, found {}",
+                self.signer_infos.len()
+            ),
+        }
+    }
+
+    /// The first value of the signed attribute of type `oid`, if present.
+    fn signed_attribute(&self, oid: &Oid) -> anyhow::Result<Option<&Any>> {
+        Ok(self
+            .signer_info()?
+            .signed_attrs
+            .as_ref()
+            .and_then(|attrs| attrs.iter().find(|attr| oid == attr.r#type))
+            .and_then(|attr| attr.values.iter().next()))
+    }
+
+    /// The origin AS number.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the AS number does not fit in 32 bits.
+    pub fn as_id(&self) -> anyhow::Result<u32> {
+        self.econtent.as_id()
+    }
+
+    /// The address family, prefix and `max_length` (if present) of each
+    /// entry, in the order in which they appear in the ROA.
+    ///
+    /// # Errors
+    ///
+    /// Each item is an error if its address family indicator, prefix or
+    /// `max_length` is invalid.
+    pub fn addresses(
+        &self,
+    ) -> impl Iterator<Item = anyhow::Result<(concrete::Afi, any::Prefix, Option<u8>)>> + '_ {
+        self.econtent
+            .ip_addr_blocks()
+            .flat_map(|roa_ip_addr_family| {
+                let afi = roa_ip_addr_family.address_family();
+                roa_ip_addr_family
+                    .addresses()
+                    .map(move |roa_ip_addr| match &afi {
+                        Ok(concrete::Afi::Ipv4) => Ok((
+                            concrete::Afi::Ipv4,
+                            any::Prefix::Ipv4(roa_ip_addr.address()?),
+                            roa_ip_addr
+                                .max_length::<Ipv4>()?
+                                .map(PrefixLength::into_primitive),
+                        )),
+                        Ok(concrete::Afi::Ipv6) => Ok((
+                            concrete::Afi::Ipv6,
+                            any::Prefix::Ipv6(roa_ip_addr.address()?),
+                            roa_ip_addr
+                                .max_length::<Ipv6>()?
+                                .map(PrefixLength::into_primitive),
+                        )),
+                        Err(_) => anyhow::bail!("invalid IP address family indicator"),
+                    })
+            })
+    }
+}
+
+impl TryFrom<RoaContentInfo> for Roa {
+    type Error = anyhow::Error;
+
+    fn try_from(value: RoaContentInfo) -> Result<Self, Self::Error> {
+        log::info!("checking for id-ct-SignedData content-type");
+        if CONTENT_SIGNED_DATA != value.content_type {
+            let msg = "invalid OID for SignedData content";
+            log::error!("{msg}");
+            anyhow::bail!(msg);
+        }
+        log::info!("trying to decode content as SignedData");
+        let content = value.content.as_bytes();
+        let signed_data: SignedData =
+            der::decode(content).context("failed to decode CMS content")?;
+
+        let encap_content_info = signed_data.encap_content_info;
+        if ID_CT_ROUTE_ORIGIN_AUTHZ != encap_content_info.content_type {
+            anyhow::bail!("invalid OID for ROA eContent");
+        }
+        log::info!("trying to decode econtent as RouteOriginAttestation");
+        let econtent_der = encap_content_info.content.ok_or_else(|| {
+            anyhow::anyhow!(
+                "ROA has no embedded eContent: detached content is not supported \
+                 (is this a detached signature, or a truncated object?)"
+            )
+        })?;
+        let econtent: RouteOriginAttestation =
+            der::decode(econtent_der.as_ref()).context("failed to decode eContent")?;
+        econtent.check_version()?;
+
+        Ok(Self {
+            econtent,
+            econtent_der,
+            signer_infos: signed_data.signer_infos,
+        })
+    }
+}
+
+/// Strip any PEM armor from `bytes`, and decode the base64 that it wraps.
+///
+/// Bytes starting with a SEQUENCE tag are taken to be DER already, and are
+/// returned unchanged.
+pub(crate) fn unarmor(bytes: &[u8]) -> anyhow::Result<Cow<'_, [u8]>> {
+    if bytes.first() == Some(&0x30) {
+        return Ok(Cow::Borrowed(bytes));
+    }
+    log::info!("trying to decode base64 from input bytes");
+    let text = std::str::from_utf8(bytes).context("input is neither DER nor base64 text")?;
+    let body = match text.find("-----BEGIN") {
+        Some(start) => {
+            let (_, body) = text[start..]
+                .split_once('\n')
+                .context("PEM armor has no content")?;
+            body.find("-----END")
+                .map(|end| &body[..end])
+                .context("PEM armor has no END line")?
+        }
+        None => text,
+    };
+    let encoded: String = body.split_ascii_whitespace().collect();
+    STANDARD
+        .decode(encoded)
+        .map(Cow::Owned)
+        .context("failed to decode base64")
+}
+
+/// Split the DER encoding of a single SEQUENCE from the start of `bytes`,
+/// returning it together with the bytes that follow.
+fn split_der(bytes: &[u8]) -> anyhow::Result<(&[u8], &[u8])> {
+    let (header, length) = match bytes {
+        [0x30, length @ 0..=0x7f, ..] => (2, usize::from(*length)),
+        [0x30, count @ 0x81..=0x84, rest @ ..] => {
+            let count = usize::from(count & 0x7f);
+            let octets = rest.get(..count).context("truncated DER length")?;
+            let length = octets
+                .iter()
+                .fold(0, |length, octet| length << 8 | usize::from(*octet));
+            (2 + count, length)
+        }
+        _ => anyhow::bail!("expected a DER-encoded SEQUENCE"),
+    };
+    let end = header
+        .checked_add(length)
+        .filter(|end| *end <= bytes.len())
+        .context("truncated DER-encoded SEQUENCE")?;
+    Ok(bytes.split_at(end))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OK_ROA: &[u8] = include_bytes!("../tests/data/ok.roa");
+    const NO_ECONTENT_ROA: &[u8] = include_bytes!("../tests/data/no-econtent.roa");
+    const TAMPERED_ROA: &[u8] = include_bytes!("../tests/data/tampered.roa");
+    const SIGNING_TIME_ROA: &[u8] = include_bytes!("../tests/data/signing-time.roa");
+    const VERSION_1_ROA: &[u8] = include_bytes!("../tests/data/version-1.roa");
+    const OK_PEM: &[u8] = include_bytes!("../tests/data/ok.pem");
+    const OK_B64: &[u8] = include_bytes!("../tests/data/ok.b64");
+    const CONCATENATED_ROA: &[u8] = include_bytes!("../tests/data/concatenated.roa");
+
+    #[test]
+    fn split_der() -> anyhow::Result<()> {
+        assert_eq!(
+            super::split_der(&[0x30, 0x02, 0x05, 0x00, 0xff])?,
+            (&[0x30, 0x02, 0x05, 0x00][..], &[0xff][..])
+        );
+        let (der, rest) = super::split_der(CONCATENATED_ROA)?;
+        assert_eq!((der.len(), rest.len()), (OK_ROA.len(), OK_ROA.len()));
+        assert_eq!(der, OK_ROA);
+        assert!(super::split_der(&[]).is_err());
+        assert!(super::split_der(&[0x04, 0x00]).is_err());
+        assert!(super::split_der(&[0x30, 0x82, 0x01]).is_err());
+        assert!(super::split_der(&OK_ROA[..100]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn concatenated() -> anyhow::Result<()> {
+        let roas = Roa::from_der_concatenated(CONCATENATED_ROA)?;
+        assert_eq!(roas.len(), 2);
+        assert_eq!(Roa::from_der_concatenated(OK_ROA)?.len(), 1);
+        let first = |roa: &Roa| -> anyhow::Result<String> {
+            let (_, prefix, _) = roa.addresses().next().context("no addresses")??;
+            Ok(prefix.to_string())
+        };
+        assert_eq!(first(&roas[0])?, "192.0.2.0/24");
+        assert_eq!(first(&roas[1])?, "192.0.2.0/25");
+
+        let mut garbage = OK_ROA.to_vec();
+        garbage.extend_from_slice(b"garbage");
+        let err = Roa::from_der_concatenated(&garbage).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "trailing data at offset {} is not a DER-encoded ROA",
+                OK_ROA.len()
+            )
+        );
+        let err = Roa::from_der_concatenated(&garbage[..OK_ROA.len() + 100]).unwrap_err();
+        assert!(err.to_string().starts_with("trailing data at offset"));
+        assert!(Roa::from_der_concatenated(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn unarmor() -> anyhow::Result<()> {
+        assert!(matches!(super::unarmor(OK_ROA)?, Cow::Borrowed(OK_ROA)));
+        assert_eq!(super::unarmor(OK_PEM)?, OK_ROA);
+        assert_eq!(super::unarmor(OK_B64)?, OK_ROA);
+        assert!(super::unarmor(b"-----BEGIN ROA-----\nMIIB\n").is_err());
+        assert!(super::unarmor(b"not base64!").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn absent_econtent() {
+        let err = Roa::from_der(NO_ECONTENT_ROA).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("ROA has no embedded eContent: detached content is not supported"));
+    }
+
+    #[test]
+    fn unsupported_version() {
+        let err = Roa::from_der(VERSION_1_ROA).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported ROA version 1: only version 0 is defined"
+        );
+    }
+
+    #[test]
+    fn verify_digest() -> anyhow::Result<()> {
+        Roa::from_der(OK_ROA)?.verify_digest()?;
+        let tampered = Roa::from_der(TAMPERED_ROA)?;
+        assert_eq!(tampered.as_id()?, 65001);
+        assert_eq!(
+            tampered.verify_digest().unwrap_err().to_string(),
+            "signed message digest does not match the ROA eContent"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn signing_time() -> anyhow::Result<()> {
+        assert_eq!(Roa::from_der(OK_ROA)?.signing_time()?, None);
+        let signing_time = Roa::from_der(SIGNING_TIME_ROA)?
+            .signing_time()?
+            .map(|time| time.to_rfc3339());
+        assert_eq!(signing_time.as_deref(), Some("2023-08-02T12:15:42+00:00"));
+        Ok(())
+    }
+}
@@ -1,13 +1,95 @@
 use predicates::{
+    function::function,
     path::eq_file,
-    str::{is_empty, starts_with},
+    str::{contains, is_empty, starts_with, PredicateStrExt},
 };
 
 const OK_TXT_PATH: &str = "tests/data/ok.txt";
+const OK_JSON_PATH: &str = "tests/data/ok.json";
+const OK_RPSL_PATH: &str = "tests/data/ok.rpsl";
+const OK_CSV_PATH: &str = "tests/data/ok.csv";
+const OK_TXT_GZ_PATH: &str = "tests/data/ok.txt.gz";
+const OK_ROA_GZ_PATH: &str = "tests/data/ok.roa.gz";
+const OK_PEM_PATH: &str = "tests/data/ok.pem";
+const OK_B64_PATH: &str = "tests/data/ok.b64";
+const OK_TSV_PATH: &str = "tests/data/ok.tsv";
+const OK_ASN_TSV_PATH: &str = "tests/data/ok-asn.tsv";
 const OK_ROA_PATH: &str = "tests/data/ok.roa";
+const SIGNING_TIME_ROA_PATH: &str = "tests/data/signing-time.roa";
+const VERSION_1_ROA_PATH: &str = "tests/data/version-1.roa";
+const TAMPERED_ROA_PATH: &str = "tests/data/tampered.roa";
+const NO_ECONTENT_ROA_PATH: &str = "tests/data/no-econtent.roa";
 const ERR_TXT_PATH: &str = "tests/data/err.txt";
 const ERR_ROA_PATH: &str = "tests/data/err.roa";
+const CONCATENATED_ROA_PATH: &str = "tests/data/concatenated.roa";
+const EXCLUDE_TXT_PATH: &str = "tests/data/exclude.txt";
 const ERR_MSG: &str = "Error:";
+const OK_LIMIT_3: &str = "\
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25
+";
+const OK_SKIP_5_LIMIT_3: &str = "\
+192.0.2.192/26
+2001:db8::/32
+2001:db8::/48
+";
+const OK_IPV4: &str = "\
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25
+192.0.2.128/25-26
+192.0.2.128/25-27
+192.0.2.192/26
+";
+const OK_IPV6_FIRST: &str = "\
+2001:db8::/32
+2001:db8::/48
+2001:db8:1::/48
+2001:db8:1::/48-52
+2001:db8:1::/48-56
+2001:db8:1:1::/64
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25
+192.0.2.128/25-26
+192.0.2.128/25-27
+192.0.2.192/26
+";
+const OK_RPSL: &str = "\
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25
+192.0.2.192/26
+2001:db8::/32
+2001:db8::/48
+2001:db8:1::/48
+2001:db8:1:1::/64
+";
+const OK_AFI_SUMMARY: &str = "\
+ipv4: 6 entries, first 192.0.2.0/24, last 192.0.2.192/26
+ipv6: 6 entries, first 2001:db8::/32, last 2001:db8:1:1::/64
+";
+const OK_EXCLUDED: &str = "\
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25
+192.0.2.128/25-27
+192.0.2.192/26
+2001:db8::/32
+2001:db8::/48
+2001:db8:1::/48
+2001:db8:1::/48-52
+2001:db8:1:1::/64
+";
+const OK_EXCLUDED_COVERED: &str = "\
+192.0.2.0/24
+192.0.2.0/25
+192.0.2.128/25-27
+2001:db8::/32
+2001:db8::/48
+2001:db8:1:1::/64
+";
 
 cases! {
     no_input {|mut cmd| {
@@ -17,6 +99,25 @@ cases! {
             .try_stdout(is_empty())?
         )
     }}
+    stdin_timeout_not_a_terminal {|mut cmd| {
+        Ok(cmd
+            .args(["--stdin-timeout", "1"])
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    stdin_timeout_piped_input {|mut cmd| {
+        Ok(cmd
+            .args(["--stdin-timeout", "1"])
+            .pipe_stdin(OK_TXT_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
     well_ordered_text_from_stdin {|mut cmd | {
         Ok(cmd
             .pipe_stdin(OK_TXT_PATH)?
@@ -35,6 +136,71 @@ cases! {
             .try_stderr(is_empty())?
         )
     }}
+    gzipped_text_from_stdin {|mut cmd| {
+        Ok(cmd
+            .pipe_stdin(OK_TXT_GZ_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    gzipped_text_from_file {|mut cmd| {
+        Ok(cmd
+            .arg(OK_TXT_GZ_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    gzipped_roa_from_stdin {|mut cmd| {
+        Ok(cmd
+            .pipe_stdin(OK_ROA_GZ_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    gzipped_roa_from_file {|mut cmd| {
+        Ok(cmd
+            .arg(OK_ROA_GZ_PATH)
+            .arg("--show-asn")
+            .assert()
+            .try_success()?
+            .try_stdout(starts_with("AS"))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    pem_roa_from_stdin {|mut cmd| {
+        Ok(cmd
+            .pipe_stdin(OK_PEM_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    base64_roa_from_file {|mut cmd| {
+        Ok(cmd
+            .arg(OK_B64_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    base64_roa_with_explicit_type {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "roa"])
+            .pipe_stdin(OK_B64_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
     mis_ordered_text_from_stdin {|mut cmd | {
         Ok(cmd
             .pipe_stdin(ERR_TXT_PATH)?
@@ -93,11 +259,1799 @@ cases! {
             .try_stderr(starts_with(ERR_MSG))?
         )
     }}
+    mis_ordered_roa_strict {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "roa", "--strict"])
+            .arg(ERR_ROA_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(starts_with(concat!(
+                "Warning: tests/data/err.roa: ROA wire order was non-canonical: ",
+                "IPv4 addresses are not in canonical order, from position 2 (192.0.2.0/24)\n",
+            )))?
+        )
+    }}
+    mis_ordered_roa_strict_severity {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "roa", "--strict", "--severity", "misorder=warn"])
+            .arg(ERR_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains("ROA wire order was non-canonical"))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    mis_ordered_roa_strict_jsonl {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "roa", "--strict", "--report-format", "jsonl", "--fix"])
+            .arg(ERR_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(concat!(
+                r#"{"afi":"ipv4","input":"tests/data/err.roa","kind":"addresses-misordered","#,
+                r#""position":2,"severity":"warning","value":"192.0.2.0/24"}"#,
+                "\n",
+                r#"{"afi":"ipv6","input":"tests/data/err.roa","kind":"addresses-misordered","#,
+                r#""position":3,"severity":"warning","value":"2001:db8:1::/48"}"#,
+                "\n",
+            ))?
+        )
+    }}
+    well_ordered_roa_strict {|mut cmd| {
+        Ok(cmd
+            .arg("--strict")
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    check_roa_internal_order {|mut cmd| {
+        Ok(cmd
+            .arg("--check-roa-internal-order")
+            .args([OK_ROA_PATH, OK_PEM_PATH])
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    check_roa_internal_order_misordered {|mut cmd| {
+        Ok(cmd
+            .arg("--check-roa-internal-order")
+            .arg(ERR_ROA_PATH)
+            .assert()
+            .try_code(2)?
+            .try_stdout(is_empty())?
+            .try_stderr(concat!(
+                "Error: tests/data/err.roa: IPv4 addresses are not in canonical order, ",
+                "from position 2 (192.0.2.0/24)\n",
+                "Error: tests/data/err.roa: IPv6 addresses are not in canonical order, ",
+                "from position 3 (2001:db8:1::/48)\n",
+            ))?
+        )
+    }}
+    check_roa_internal_order_text_input {|mut cmd| {
+        Ok(cmd
+            .arg("--check-roa-internal-order")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("requires ROA input"))?
+        )
+    }}
+    line_ending_crlf {|mut cmd| {
+        Ok(cmd
+            .args(["--line-ending", "crlf"])
+            .write_stdin("2001:db8::/32-48\n192.0.2.0/24\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout("192.0.2.0/24\r\n2001:db8::/32-48\r\n")?
+            .try_stderr("Error: line 2: item 192.0.2.0/24 is mis-ordered\n")?
+        )
+    }}
+    #[cfg(feature = "json")]
+    line_ending_crlf_json {|mut cmd| {
+        Ok(cmd
+            .args(["--line-ending", "crlf", "--output-type", "json"])
+            .write_stdin("192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("[{\"prefix\":\"192.0.2.0/24\"}]\r\n")?
+        )
+    }}
+    line_ending_lf {|mut cmd| {
+        Ok(cmd
+            .args(["--line-ending", "lf"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+        )
+    }}
+    concatenated_roas {|mut cmd| {
+        Ok(cmd
+            .args(["--fix", "--afi", "ipv4"])
+            .arg(CONCATENATED_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(concat!(
+                "192.0.2.0/24\n",
+                "192.0.2.0/25\n",
+                "192.0.2.128/25\n",
+                "192.0.2.128/25-26\n",
+                "192.0.2.128/25-27\n",
+                "192.0.2.192/26\n",
+                "198.51.100.0/24\n",
+            ))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    concatenated_roas_validated_separately {|mut cmd| {
+        Ok(cmd
+            .arg(CONCATENATED_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stderr(is_empty())?
+        )
+    }}
+    concatenated_roas_trailing_garbage {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "roa"])
+            .write_stdin([include_bytes!("data/ok.roa").as_slice(), b"garbage"].concat())
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("trailing data at offset 1863 is not a DER-encoded ROA"))?
+        )
+    }}
+    afi_summary {|mut cmd| {
+        Ok(cmd
+            .arg("--afi-summary")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_AFI_SUMMARY)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    limit {|mut cmd| {
+        Ok(cmd
+            .args(["--limit", "3"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_LIMIT_3)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    skip_and_limit {|mut cmd| {
+        Ok(cmd
+            .args(["--skip", "5", "--limit", "3"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_SKIP_5_LIMIT_3)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    skip_still_validates {|mut cmd| {
+        Ok(cmd
+            .args(["--skip", "100"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(starts_with(ERR_MSG))?
+        )
+    }}
+    explicit_equal_flagged {|mut cmd| {
+        Ok(cmd
+            .write_stdin("10.0.0.0/8-8\n")
+            .assert()
+            .try_failure()?
+            .try_stdout("10.0.0.0/8\n")?
+            .try_stderr(starts_with(ERR_MSG))?
+        )
+    }}
+    no_implicit_collapse {|mut cmd| {
+        Ok(cmd
+            .arg("--no-implicit-collapse")
+            .write_stdin("10.0.0.0/8-8\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-8\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    host_bits_masked {|mut cmd| {
+        Ok(cmd
+            .write_stdin("10.0.0.5/8\n2001:db8::1/32-48\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8\n2001:db8::/32-48\n")?
+            .try_stderr(
+                "Warning: masked host bits of 10.0.0.5/8, giving 10.0.0.0/8\n\
+                 Warning: masked host bits of 2001:db8::1/32, giving 2001:db8::/32\n",
+            )?
+        )
+    }}
+    host_bits_masked_bracketed {|mut cmd| {
+        Ok(cmd
+            .write_stdin("[2001:db8::1]/64\n")
+            .assert()
+            .try_success()?
+            .try_stdout("2001:db8::/64\n")?
+            .try_stderr("Warning: masked host bits of 2001:db8::1/64, giving 2001:db8::/64\n")?
+        )
+    }}
+    host_bits_rejected_bracketed_strict {|mut cmd| {
+        Ok(cmd
+            .arg("--strict")
+            .write_stdin("[2001:db8::1]/64\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("(did you mean 2001:db8::/64?)"))?
+        )
+    }}
+    host_bits_masked_vrp_csv {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "vrp-csv"])
+            .write_stdin("AS65000,10.0.0.5/8,8\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8\n")?
+            .try_stderr("Warning: masked host bits of 10.0.0.5/8, giving 10.0.0.0/8\n")?
+        )
+    }}
+    host_bits_rejected_vrp_csv_strict {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "vrp-csv", "--strict"])
+            .write_stdin("AS65000,10.0.0.5/8,8\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("(did you mean 10.0.0.0/8?)"))?
+        )
+    }}
+    host_bits_masked_rpsl {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "rpsl"])
+            .write_stdin("route: 10.0.0.5/8\norigin: AS65000\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8\n")?
+            .try_stderr("Warning: masked host bits of 10.0.0.5/8, giving 10.0.0.0/8\n")?
+        )
+    }}
+    host_bits_rejected_rpsl_strict {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "rpsl", "--strict"])
+            .write_stdin("route: 10.0.0.5/8\norigin: AS65000\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("(did you mean 10.0.0.0/8?)"))?
+        )
+    }}
+    host_bits_rejected_strict {|mut cmd| {
+        Ok(cmd
+            .arg("--strict")
+            .write_stdin("10.0.0.5/8\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("has host bits set beyond its length (did you mean 10.0.0.0/8?)"))?
+        )
+    }}
+    keep_redundant_maxlength {|mut cmd| {
+        Ok(cmd
+            .arg("--keep-redundant-maxlength")
+            .write_stdin("10.0.0.0/8-8\n10.0.0.0/8-16\n192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-8\n10.0.0.0/8-16\n192.0.2.0/24\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    keep_redundant_maxlength_json {|mut cmd| {
+        Ok(cmd
+            .args(["--keep-redundant-maxlength", "--output-type", "jsonl"])
+            .write_stdin("10.0.0.0/8-8\n")
+            .assert()
+            .try_success()?
+            .try_stdout(contains("\"maxLength\":8"))?
+        )
+    }}
+    tsv_output {|mut cmd| {
+        Ok(cmd
+            .args(["--format", "tsv"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TSV_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    tsv_output_roa {|mut cmd| {
+        Ok(cmd
+            .args(["--format", "tsv"])
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_ASN_TSV_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    tsv_output_asn {|mut cmd| {
+        Ok(cmd
+            .args(["--format", "tsv", "--asn", "65000"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_ASN_TSV_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    warn_afi_mismatch_flagged {|mut cmd| {
+        Ok(cmd
+            .arg("--warn-afi-mismatch")
+            .write_stdin("192.0.2.0/24\n::ffff:192.0.2.0/120\n")
+            .assert()
+            .try_success()?
+            .try_stderr(contains("Warning: item ::ffff:192.0.2.0/120"))?
+        )
+    }}
+    warn_afi_mismatch_unflagged {|mut cmd| {
+        Ok(cmd
+            .arg("--warn-afi-mismatch")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    warn_afi_mismatch_loopback_unflagged {|mut cmd| {
+        Ok(cmd
+            .arg("--warn-afi-mismatch")
+            .write_stdin("::/128\n::1/128\n")
+            .assert()
+            .try_success()?
+            .try_stdout("::/128\n::1/128\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    human_report {|mut cmd| {
+        Ok(cmd
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(starts_with("Error: line 3: item 2001:db8:1::/48 is mis-ordered\n"))?
+        )
+    }}
+    stream_check {|mut cmd| {
+        Ok(cmd
+            .arg("--stream-check")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    stream_check_stops_at_first_error {|mut cmd| {
+        Ok(cmd
+            .arg("--stream-check")
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_code(2)?
+            .try_stdout(is_empty())?
+            .try_stderr("Error: line 3: item 2001:db8:1::/48 is mis-ordered\n")?
+        )
+    }}
+    stream_check_continues_past_warnings {|mut cmd| {
+        Ok(cmd
+            .args(["--stream-check", "--severity", "misorder=warn"])
+            .write_stdin("10.0.0.0/16\n10.0.0.0/8\n10.0.0.0/8\n")
+            .assert()
+            .try_code(3)?
+            .try_stderr(
+                "Warning: line 2: item 10.0.0.0/8 is mis-ordered\n\
+                 Error: line 3: item 10.0.0.0/8 is a duplicate\n",
+            )?
+        )
+    }}
+    #[cfg(feature = "json")]
+    json_report {|mut cmd| {
+        Ok(cmd
+            .args(["--report-format", "json"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(starts_with(
+                r#"[{"kind":"misordered","line":3,"severity":"error","value":"2001:db8:1::/48"},"#
+            ))?
+        )
+    }}
+    afi_ipv4 {|mut cmd| {
+        Ok(cmd
+            .args(["--afi", "ipv4"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_IPV4)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    afi_ignores_other_family_ordering {|mut cmd| {
+        Ok(cmd
+            .args(["--afi", "ipv6"])
+            .write_stdin("2001:db8::/32\n10.0.0.0/8\n2001:db8::/48\n192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("2001:db8::/32\n2001:db8::/48\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    jsonl_report {|mut cmd| {
+        Ok(cmd
+            .args(["--report-format", "jsonl"])
+            .write_stdin("10.0.0.0/8\n10.0.0.0/8\n")
+            .assert()
+            .try_code(3)?
+            .try_stderr(concat!(
+                r#"{"kind":"duplicate","line":2,"severity":"error","value":"10.0.0.0/8"}"#,
+                "\n"
+            ))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    validate {|mut cmd| {
+        Ok(cmd
+            .arg("--validate")
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_code(2)?
+            .try_stdout(starts_with(concat!(
+                r#"{"kind":"misordered","line":3,"severity":"error","value":"2001:db8:1::/48"}"#,
+                "\n"
+            )))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    validate_clean {|mut cmd| {
+        Ok(cmd
+            .arg("--validate")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    tap_report {|mut cmd| {
+        Ok(cmd
+            .args(["--report-format", "tap"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(starts_with("1..12\nnot ok 1 - misordered at line 3: 2001:db8:1::/48\n"))?
+        )
+    }}
+    tap_report_ok {|mut cmd| {
+        Ok(cmd
+            .args(["--report-format", "tap"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stderr("1..1\nok 1 - input is canonical\n")?
+        )
+    }}
+    output_sort_key {|mut cmd| {
+        Ok(cmd
+            .args(["--output-sort-key", "ipv6-first"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_IPV6_FIRST)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    warn_nonaggregatable {|mut cmd| {
+        Ok(cmd
+            .arg("--warn-nonaggregatable")
+            .write_stdin("10.0.0.0/10\n10.64.0.0/10\n10.128.0.0/10\n10.192.0.0/10\n")
+            .assert()
+            .try_success()?
+            .try_stderr("Warning: 4 entries could be aggregated into 10.0.0.0/8-10\n")?
+        )
+    }}
+    aggregate {|mut cmd| {
+        Ok(cmd
+            .args(["--aggregate", "--fix"])
+            .write_stdin("10.0.0.0/24\n10.0.0.0/8-24\n192.0.2.0/24\n192.0.2.0/25\n192.0.2.128/25\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-24\n192.0.2.0/24-25\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    aggregate_grouped_by_asn {|mut cmd| {
+        Ok(cmd
+            .args(["--aggregate", "--group-by-asn"])
+            .write_stdin("AS65000 10.0.0.0/8-24\nAS65001 10.0.0.0/24\nAS65000 10.0.0.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("AS65000:\n10.0.0.0/8-24\nAS65001:\n10.0.0.0/24\n")?
+        )
+    }}
+    report_overlaps {|mut cmd| {
+        Ok(cmd
+            .arg("--report-overlaps")
+            .write_stdin("10.0.0.0/8-24\n10.0.0.0/16\n10.1.0.0/16-25\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-24\n10.0.0.0/16\n10.1.0.0/16-25\n")?
+            .try_stderr("Overlap: 10.0.0.0/8-24 covers 10.0.0.0/16\n")?
+        )
+    }}
+    assume_sorted {|mut cmd| {
+        Ok(cmd
+            .arg("--assume-sorted")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    assume_sorted_violated {|mut cmd| {
+        Ok(cmd
+            .arg("--assume-sorted")
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(starts_with("Error: line 3:"))?
+        )
+    }}
+    severity_all_warn {|mut cmd| {
+        Ok(cmd
+            .args(["--severity", "misorder=warn,redundant=warn,duplicate=warn"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(starts_with("Warning: line 3: item 2001:db8:1::/48 is mis-ordered\n"))?
+        )
+    }}
+    severity_redundant_ignored {|mut cmd| {
+        Ok(cmd
+            .args(["--severity", "redundant=ignore"])
+            .write_stdin("10.0.0.0/8-8\n")
+            .assert()
+            .try_success()?
+            .try_stderr(is_empty())?
+        )
+    }}
+    severity_redundant_error_only {|mut cmd| {
+        Ok(cmd
+            .args(["--severity", "misorder=ignore", "--severity", "duplicate=ignore"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(starts_with(
+                "Error: line 7: item 192.0.2.0/24-24 has unnecessarily specified max_length\n"
+            ))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    jsonl_output {|mut cmd| {
+        Ok(cmd
+            .args(["--format", "jsonl"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(function(|output: &str| {
+                output.lines().count() == 12
+                    && output
+                        .lines()
+                        .all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok())
+            }).from_utf8())?
+            .try_stdout(contains(r#"{"maxLength":26,"prefix":"192.0.2.128/25"}"#))?
+        )
+    }}
+    show_signing_time {|mut cmd| {
+        Ok(cmd
+            .arg("--show-signing-time")
+            .arg(SIGNING_TIME_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr("tests/data/signing-time.roa: signed at 2023-08-02T12:15:42+00:00\n")?
+        )
+    }}
+    show_signing_time_unknown {|mut cmd| {
+        Ok(cmd
+            .arg("--show-signing-time")
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stderr("tests/data/ok.roa: signing time unknown\n")?
+        )
+    }}
+    roa_version_1 {|mut cmd| {
+        Ok(cmd
+            .arg(VERSION_1_ROA_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("unsupported ROA version 1"))?
+        )
+    }}
+    verify_roa {|mut cmd| {
+        Ok(cmd
+            .arg("--verify")
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    verify_tampered_roa {|mut cmd| {
+        Ok(cmd
+            .arg("--verify")
+            .arg(TAMPERED_ROA_PATH)
+            .assert()
+            .try_code(1)?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("signed message digest does not match the ROA eContent"))?
+        )
+    }}
+    tampered_roa_unverified {|mut cmd| {
+        Ok(cmd
+            .arg(TAMPERED_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+        )
+    }}
+    verify_requires_roa {|mut cmd| {
+        Ok(cmd
+            .arg("--verify")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("--verify requires ROA input"))?
+        )
+    }}
+    roa_detected_on_stdin {|mut cmd| {
+        Ok(cmd
+            .pipe_stdin(OK_ROA_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    text_detected_on_stdin {|mut cmd| {
+        Ok(cmd
+            .write_stdin("0.0.0.0/0\n2001:db8::/32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("0.0.0.0/0\n2001:db8::/32\n")?
+        )
+    }}
+    roa_as_text_hint {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "text"])
+            .pipe_stdin(OK_ROA_PATH)?
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("input looks like binary; did you mean `-t roa`?"))?
+        )
+    }}
+    roa_inferred_from_extension {|mut cmd| {
+        Ok(cmd
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    roa_inferred_from_extension_logged {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains("inferred ROA input from extension of tests/data/ok.roa"))?
+        )
+    }}
+    text_inferred_from_extension {|mut cmd| {
+        // content that would be detected as base64-encoded ROA input
+        let path = std::env::temp_dir()
+            .join(format!("roasort-inferred-{}.txt", std::process::id()));
+        _ = std::fs::copy(OK_B64_PATH, &path)?;
+        let assert = cmd
+            .arg("-vv")
+            .arg(&path)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("inferred text input from extension"))?
+            .try_stderr(contains("line 1: 'MI"))?;
+        std::fs::remove_file(&path)?;
+        Ok(assert)
+    }}
+    text_inferred_through_gzip_extension {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_TXT_GZ_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains("inferred text input from extension of tests/data/ok.txt.gz"))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    json_inferred_from_extension {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_JSON_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains("inferred JSON input from extension of tests/data/ok.json"))?
+        )
+    }}
+    csv_inferred_from_extension {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_CSV_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains("inferred VRP CSV input from extension of tests/data/ok.csv"))?
+        )
+    }}
+    rpsl_inferred_from_extension {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_RPSL_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_RPSL)?
+            .try_stderr(contains("inferred RPSL input from extension of tests/data/ok.rpsl"))?
+        )
+    }}
+    pem_inferred_from_extension {|mut cmd| {
+        Ok(cmd
+            .arg("-vv")
+            .arg(OK_PEM_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(contains(
+                "inferred PEM-armored ROA input from extension of tests/data/ok.pem",
+            ))?
+        )
+    }}
+    explicit_input_type_overrides_extension {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "text"])
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("did you mean `-t roa`?"))?
+        )
+    }}
+    rpsl_input {|mut cmd| {
+        Ok(cmd
+            .arg(OK_RPSL_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_RPSL)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    rpsl_input_from_stdin {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "rpsl"])
+            .write_stdin("route6: 2001:db8::/32\n\nroute: 192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(starts_with("Error: line 2: item 192.0.2.0/24 is mis-ordered\n"))?
+        )
+    }}
+    vrp_csv_input {|mut cmd| {
+        Ok(cmd
+            .arg(OK_CSV_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    vrp_csv_input_show_asn {|mut cmd| {
+        Ok(cmd
+            .args(["--show-asn", "--limit", "2"])
+            .arg(OK_CSV_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout("AS65000 192.0.2.0/24\nAS65000 192.0.2.0/25\n")?
+        )
+    }}
+    vrp_csv_input_group_by_asn {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "vrp-csv", "--group-by-asn"])
+            .write_stdin(concat!(
+                "ASN,IP Prefix,Max Length,Trust Anchor\n",
+                "AS65001,2001:db8::/32,48,example\n",
+                "AS65000,192.0.2.0/24,24,example\n",
+                "AS65001,192.0.2.0/25,25,example\n",
+            ))
+            .assert()
+            .try_success()?
+            .try_stdout("AS65000:\n192.0.2.0/24\nAS65001:\n192.0.2.0/25\n2001:db8::/32-48\n")?
+        )
+    }}
+    vrp_csv_output_roa_input {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "vrp-csv"])
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(concat!(
+                "ASN,IP Prefix,Max Length\n",
+                "AS65000,192.0.2.0/24,24\n",
+                "AS65000,192.0.2.0/25,25\n",
+                "AS65000,192.0.2.128/25,25\n",
+                "AS65000,192.0.2.128/25,26\n",
+                "AS65000,192.0.2.128/25,27\n",
+                "AS65000,192.0.2.192/26,26\n",
+                "AS65000,2001:db8::/32,32\n",
+                "AS65000,2001:db8::/48,48\n",
+                "AS65000,2001:db8:1::/48,48\n",
+                "AS65000,2001:db8:1::/48,52\n",
+                "AS65000,2001:db8:1::/48,56\n",
+                "AS65000,2001:db8:1:1::/64,64\n",
+            ))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    vrp_csv_output_asn {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "vrp-csv", "--asn", "65001"])
+            .write_stdin("192.0.2.0/24\n2001:db8::/32-48\n")
+            .assert()
+            .try_success()?
+            .try_stdout("ASN,IP Prefix,Max Length\nAS65001,192.0.2.0/24,24\nAS65001,2001:db8::/32,48\n")?
+        )
+    }}
+    vrp_csv_output_round_trip {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "vrp-csv"])
+            .arg(OK_CSV_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(function(|out: &str| {
+                out.lines().skip(1).eq(include_str!("data/ok.csv")
+                    .lines()
+                    .skip(1)
+                    .map(|line| line.trim_end_matches(",example")))
+            }))?
+        )
+    }}
+    vrp_csv_output_requires_asn {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "vrp-csv"])
+            .write_stdin("192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("VRP CSV output requires an origin AS number"))?
+        )
+    }}
+    check_subcommand {|mut cmd| {
+        Ok(cmd
+            .arg("check")
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout(is_empty())?
+            .try_stderr("Error: line 2: item 192.0.2.0/24 is mis-ordered\n")?
+        )
+    }}
+    check_subcommand_with_input {|mut cmd| {
+        Ok(cmd
+            .args(["check", "--afi", "ipv4", OK_TXT_PATH])
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    check_subcommand_validate {|mut cmd| {
+        Ok(cmd
+            .args(["check", "--validate"])
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout(contains(r#""kind":"misordered""#))?
+        )
+    }}
+    check_subcommand_rejects_fix {|mut cmd| {
+        Ok(cmd
+            .args(["check", "--fix", OK_TXT_PATH])
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("use sort instead"))?
+        )
+    }}
+    no_subcommand_prints_canonical_form {|mut cmd| {
+        Ok(cmd
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr("Error: line 2: item 192.0.2.0/24 is mis-ordered\n")?
+        )
+    }}
+    sort_subcommand {|mut cmd| {
+        Ok(cmd
+            .arg("sort")
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    convert_subcommand {|mut cmd| {
+        Ok(cmd
+            .args(["convert", "--from", "roa", "--to", "text", OK_ROA_PATH])
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    convert_subcommand_stdin {|mut cmd| {
+        Ok(cmd
+            .args(["convert", "--from", "text", "--to", "tsv"])
+            .write_stdin("2001:db8::/32-48\n192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\t\n2001:db8::/32\t48\n")?
+        )
+    }}
+    convert_subcommand_requires_types {|mut cmd| {
+        Ok(cmd
+            .args(["convert", "--from", "roa", OK_ROA_PATH])
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("--to <TO>"))?
+        )
+    }}
+    diff_subcommand {|mut cmd| {
+        Ok(cmd
+            .args(["diff", OK_TXT_PATH, OK_RPSL_PATH])
+            .assert()
+            .try_failure()?
+            .try_stdout(concat!(
+                "-192.0.2.128/25-26\n",
+                "-192.0.2.128/25-27\n",
+                "-2001:db8:1::/48-52\n",
+                "-2001:db8:1::/48-56\n",
+            ))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    diff_subcommand_added {|mut cmd| {
+        Ok(cmd
+            .args(["diff", "--afi", "ipv6", OK_RPSL_PATH, OK_ROA_PATH])
+            .assert()
+            .try_failure()?
+            .try_stdout("+2001:db8:1::/48-52\n+2001:db8:1::/48-56\n")?
+        )
+    }}
+    diff_subcommand_same {|mut cmd| {
+        Ok(cmd
+            .args(["diff", OK_TXT_PATH, OK_ROA_PATH])
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    diff_subcommand_requires_two_inputs {|mut cmd| {
+        Ok(cmd
+            .args(["diff", OK_TXT_PATH])
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("diff requires exactly two inputs"))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    json_input {|mut cmd| {
+        Ok(cmd
+            .arg(OK_JSON_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    json_input_invalid_max_length {|mut cmd| {
+        Ok(cmd
+            .args(["-t", "json"])
+            .write_stdin(r#"[{"prefix": "192.0.2.0/24", "maxLength": 16}]"#)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("less than prefix length"))?
+        )
+    }}
+    group_by_asn {|mut cmd| {
+        Ok(cmd
+            .arg("--group-by-asn")
+            .write_stdin("AS65001 2001:db8::/32\nAS65000:\n198.51.100.0/24\n192.0.2.0/24\nAS65001 10.0.0.0/8\n")
+            .assert()
+            .try_success()?
+            .try_stdout("AS65000:\n192.0.2.0/24\n198.51.100.0/24\nAS65001:\n10.0.0.0/8\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    group_by_asn_with_roa {|mut cmd| {
+        let expect = format!(
+            "AS64512:\n10.0.0.0/8\nAS65000:\n{}",
+            std::fs::read_to_string(OK_TXT_PATH)?
+        );
+        Ok(cmd
+            .arg("--group-by-asn")
+            .arg("-")
+            .arg(OK_ROA_PATH)
+            .write_stdin("AS64512 10.0.0.0/8\n")
+            .assert()
+            .try_success()?
+            .try_stdout(expect)?
+        )
+    }}
+    group_by_asn_missing_asn {|mut cmd| {
+        Ok(cmd
+            .arg("--group-by-asn")
+            .write_stdin("10.0.0.0/8\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("no origin AS given for item"))?
+        )
+    }}
+    show_asn {|mut cmd| {
+        Ok(cmd
+            .arg("--show-asn")
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(concat!(
+                "AS65000 192.0.2.0/24\n",
+                "AS65000 192.0.2.0/25\n",
+                "AS65000 192.0.2.128/25\n",
+                "AS65000 192.0.2.128/25-26\n",
+                "AS65000 192.0.2.128/25-27\n",
+                "AS65000 192.0.2.192/26\n",
+                "AS65000 2001:db8::/32\n",
+                "AS65000 2001:db8::/48\n",
+                "AS65000 2001:db8:1::/48\n",
+                "AS65000 2001:db8:1::/48-52\n",
+                "AS65000 2001:db8:1::/48-56\n",
+                "AS65000 2001:db8:1:1::/64\n",
+            ))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    show_asn_requires_roa {|mut cmd| {
+        Ok(cmd
+            .arg("--show-asn")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("--show-asn requires ROA input"))?
+        )
+    }}
+    comments_and_blank_lines {|mut cmd| {
+        Ok(cmd
+            .write_stdin("# servers\n192.0.2.0/24  # web\n\n# ipv6\n2001:db8::/32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    comments_ignored_for_ordering {|mut cmd| {
+        Ok(cmd
+            .args(["--report-format", "json"])
+            .write_stdin("# first\n2001:db8::/32\n\n192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stderr(contains(r#""line":4"#))?
+        )
+    }}
+    comments_counted_for_line_numbers {|mut cmd| {
+        Ok(cmd
+            .arg("--assume-sorted")
+            .write_stdin("# first\n2001:db8::/32\n\n192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("line 4: item 192.0.2.0/24 is out of order"))?
+        )
+    }}
+    parse_error_location {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0/24\n192.0.2.0/33\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("line 2: '192.0.2.0/33'"))?
+        )
+    }}
+    surrounding_whitespace {|mut cmd| {
+        Ok(cmd
+            .write_stdin("\t192.0.2.0/24\n2001:db8::/32  \n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    internal_whitespace {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0 /24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+        )
+    }}
+    trim_list_markers {|mut cmd| {
+        Ok(cmd
+            .arg("--trim-list-markers")
+            .write_stdin("- 10.0.0.0/8-16\n* 192.0.2.0/24,\n2001:db8::/32,\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-16\n192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    list_markers_untrimmed {|mut cmd| {
+        Ok(cmd
+            .write_stdin("- 10.0.0.0/8-16\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+        )
+    }}
+    normalize_slashes {|mut cmd| {
+        Ok(cmd
+            .arg("--normalize-slashes")
+            .write_stdin("192.0.2.0\u{ff0f}24\n2001:db8::\u{2044}32-48\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n2001:db8::/32-48\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    slashes_unnormalized {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0\u{ff0f}24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+        )
+    }}
+    count {|mut cmd| {
+        Ok(cmd
+            .arg("--count")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(
+                "Count: 12 entries (6 IPv4, 6 IPv6), 0 duplicates removed, \
+                 4 with explicit max_length\n",
+            )?
+        )
+    }}
+    #[cfg(feature = "json")]
+    count_json_output {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "count-json"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout("{\"total\":12,\"ipv4\":6,\"ipv6\":6,\"duplicatesRemoved\":3}\n")?
+        )
+    }}
+    summary_table_output {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "summary-table"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(concat!(
+                "total entries           12\n",
+                "ipv4 entries             6\n",
+                "ipv6 entries             6\n",
+                "duplicates removed       3\n",
+                "redundant max_length     2\n",
+                "covered entries          5\n",
+                "ipv4 min prefix length  24\n",
+                "ipv4 max prefix length  26\n",
+                "ipv6 min prefix length  32\n",
+                "ipv6 max prefix length  64\n",
+            ))?
+        )
+    }}
+    diff {|mut cmd| {
+        Ok(cmd
+            .arg("--diff")
+            .write_stdin("192.0.2.0/24\n2001:db8::/32\n192.0.2.0/25\n")
+            .assert()
+            .try_failure()?
+            .try_stdout("192.0.2.0/24\n192.0.2.0/25\n2001:db8::/32\n")?
+            .try_stderr(concat!(
+                "--- input\n",
+                "+++ canonical\n",
+                "@@ -1,3 +1,3 @@\n",
+                " 192.0.2.0/24\n",
+                "-2001:db8::/32\n",
+                " 192.0.2.0/25\n",
+                "+2001:db8::/32\n",
+                "Error: line 3: item 192.0.2.0/25 is mis-ordered\n",
+            ))?
+        )
+    }}
+    diff_canonical {|mut cmd| {
+        Ok(cmd
+            .arg("--diff")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    exit_code_misorder {|mut cmd| {
+        Ok(cmd
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n192.0.2.0/24-24\n")
+            .assert()
+            .try_code(2)?
+        )
+    }}
+    exit_code_duplicate {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24\n192.0.2.0/25-25\n")
+            .assert()
+            .try_code(3)?
+        )
+    }}
+    exit_code_redundant {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0/24-24\n")
+            .assert()
+            .try_code(4)?
+        )
+    }}
+    exit_code_parse_error {|mut cmd| {
+        Ok(cmd
+            .write_stdin("192.0.2.0/33\n")
+            .assert()
+            .try_code(1)?
+        )
+    }}
+    exit_codes_in_help {|mut cmd| {
+        Ok(cmd
+            .arg("--help")
+            .assert()
+            .try_success()?
+            .try_stdout(contains("2  an entry is mis-ordered"))?
+        )
+    }}
+    first_diff {|mut cmd| {
+        Ok(cmd
+            .arg("--first-diff")
+            .write_stdin("192.0.2.0/24\n2001:db8::/32\n192.0.2.0/25\n192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr("Error: line 2: found 2001:db8::/32, expected 192.0.2.0/25\n")?
+        )
+    }}
+    first_diff_ok {|mut cmd| {
+        Ok(cmd
+            .arg("--first-diff")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?
+        )
+    }}
+    no_dedup {|mut cmd| {
+        Ok(cmd
+            .arg("--no-dedup")
+            .write_stdin("2001:db8::/32\n192.0.2.0/24\n2001:db8::/32\n192.0.2.0/24\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout("192.0.2.0/24\n192.0.2.0/24\n2001:db8::/32\n2001:db8::/32\n")?
+            .try_stderr("Error: line 2: item 192.0.2.0/24 is mis-ordered\n")?
+        )
+    }}
+    no_dedup_sorted_input {|mut cmd| {
+        Ok(cmd
+            .arg("--no-dedup")
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24\n2001:db8::/32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n192.0.2.0/24\n2001:db8::/32\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    no_dedup_assume_sorted {|mut cmd| {
+        Ok(cmd
+            .args(["--no-dedup", "--assume-sorted"])
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24\n2001:db8::/32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n192.0.2.0/24\n2001:db8::/32\n")?
+        )
+    }}
+    implicit_maxlength_host {|mut cmd| {
+        Ok(cmd
+            .args(["--implicit-maxlength", "host"])
+            .write_stdin("10.0.0.0/8-8\n10.0.0.0/8-16\n192.0.2.1/32\n2001:db8::/32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-8\n10.0.0.0/8-16\n192.0.2.1/32\n2001:db8::/32-128\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    implicit_maxlength_host_order {|mut cmd| {
+        Ok(cmd
+            .args(["--implicit-maxlength", "host"])
+            .write_stdin("10.0.0.0/8\n10.0.0.0/8-16\n")
+            .assert()
+            .try_code(2)?
+            .try_stdout("10.0.0.0/8-16\n10.0.0.0/8-32\n")?
+            .try_stderr("Error: line 2: item 10.0.0.0/8-16 is mis-ordered\n")?
+        )
+    }}
+    implicit_maxlength_prefix {|mut cmd| {
+        Ok(cmd
+            .args(["--implicit-maxlength", "prefix"])
+            .write_stdin("10.0.0.0/8\n10.0.0.0/8-16\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8\n10.0.0.0/8-16\n")?
+        )
+    }}
+    fold_maxlength {|mut cmd| {
+        Ok(cmd
+            .arg("--fold-maxlength")
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24-26\n192.0.2.0/25\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24-26\n192.0.2.0/25\n")?
+            .try_stderr(
+                "Warning: folded 192.0.2.0/24 into 192.0.2.0/24-26, changing the set of entries\n",
+            )?
+        )
+    }}
+    dash_prefixed_file_after_separator {|mut cmd| {
+        Ok(cmd
+            .current_dir("tests/data")
+            .args(["--", "-ok.txt"])
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    warn_open_maxlength_flagged {|mut cmd| {
+        Ok(cmd
+            .arg("--warn-open-maxlength")
+            .write_stdin("10.0.0.0/8-32\n192.0.2.0/24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-32\n192.0.2.0/24\n")?
+            .try_stderr(
+                "Warning: item 10.0.0.0/8-32 authorizes all more-specifics down to host routes\n",
+            )?
+        )
+    }}
+    max_expansion {|mut cmd| {
+        Ok(cmd
+            .args(["--max-expansion", "8"])
+            .write_stdin("10.0.0.0/8-16\n192.0.2.0/24-32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-16\n192.0.2.0/24-32\n")?
+            .try_stderr("")?
+        )
+    }}
+    max_expansion_exceeded {|mut cmd| {
+        Ok(cmd
+            .args(["--max-expansion", "8"])
+            .write_stdin("10.0.0.0/8-24\n192.0.2.0/24-32\n")
+            .assert()
+            .try_success()?
+            .try_stdout("10.0.0.0/8-24\n192.0.2.0/24-32\n")?
+            .try_stderr(
+                "Warning: item 10.0.0.0/8-24 authorizes 16 levels of more-specifics, more than 8\n",
+            )?
+        )
+    }}
+    max_expansion_fail {|mut cmd| {
+        Ok(cmd
+            .args(["--max-expansion", "8", "--fail-on-expansion"])
+            .write_stdin("10.0.0.0/8-24\n")
+            .assert()
+            .try_code(5)?
+            .try_stdout("10.0.0.0/8-24\n")?
+            .try_stderr(
+                "Error: item 10.0.0.0/8-24 authorizes 16 levels of more-specifics, more than 8\n",
+            )?
+        )
+    }}
+    warn_open_maxlength_unflagged {|mut cmd| {
+        Ok(cmd
+            .args(["--warn-open-maxlength", "--severity", "redundant=ignore"])
+            .write_stdin("192.0.2.0/24-24\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    multiple_inputs {|mut cmd| {
+        let expect = format!("10.0.0.0/8\n{}", std::fs::read_to_string(OK_TXT_PATH)?);
+        Ok(cmd
+            .arg("-")
+            .arg(OK_ROA_PATH)
+            .write_stdin("10.0.0.0/8\n")
+            .assert()
+            .try_success()?
+            .try_stdout(expect)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    multiple_inputs_duplicates {|mut cmd| {
+        Ok(cmd
+            .arg(OK_TXT_PATH)
+            .arg(OK_ROA_PATH)
+            .assert()
+            .try_code(3)?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+        )
+    }}
+    multiple_inputs_parse_error {|mut cmd| {
+        Ok(cmd
+            .arg(OK_TXT_PATH)
+            .arg(OK_ROA_PATH)
+            .args(["-t", "text"])
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("failed to read input tests/data/ok.roa"))?
+        )
+    }}
+    stdin_flag_overrides_path {|mut cmd| {
+        Ok(cmd
+            .arg("--stdin")
+            .arg(ERR_ROA_PATH)
+            .pipe_stdin(OK_TXT_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    path_overrides_default_stdin {|mut cmd| {
+        Ok(cmd
+            .arg(OK_TXT_PATH)
+            .pipe_stdin(ERR_TXT_PATH)?
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    exclude_file {|mut cmd| {
+        Ok(cmd
+            .args(["--exclude-file", EXCLUDE_TXT_PATH])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_EXCLUDED)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    exclude_covered {|mut cmd| {
+        Ok(cmd
+            .args(["--exclude-file", EXCLUDE_TXT_PATH, "--exclude-covered"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(OK_EXCLUDED_COVERED)?
+            .try_stderr(is_empty())?
+        )
+    }}
+    canonical_form_draft {|mut cmd| {
+        Ok(cmd
+            .args(["--canonical-form", "draft"])
+            .write_stdin("9.0.0.0/8\n10.0.0.0/8\n")
+            .assert()
+            .try_success()?
+            .try_stdout("9.0.0.0/8\n10.0.0.0/8\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    canonical_form_legacy {|mut cmd| {
+        Ok(cmd
+            .args(["--canonical-form", "legacy"])
+            .write_stdin("9.0.0.0/8\n10.0.0.0/8\n")
+            .assert()
+            .try_failure()?
+            .try_stdout("10.0.0.0/8\n9.0.0.0/8\n")?
+            .try_stderr("Error: line 2: item 10.0.0.0/8 is mis-ordered\n")?
+        )
+    }}
+    annotate_coverage {|mut cmd| {
+        Ok(cmd
+            .args(["--annotate-coverage", "--no-implicit-collapse"])
+            .write_stdin("10.0.0.0/8\n10.0.0.0/8-24\n192.0.2.0/24-24\n192.0.2.0/24-25\n")
+            .assert()
+            .try_success()?
+            .try_stdout(
+                "10.0.0.0/8  # 0 more-specific levels\n\
+                 10.0.0.0/8-24  # 16 more-specific levels\n\
+                 192.0.2.0/24-24  # 0 more-specific levels\n\
+                 192.0.2.0/24-25  # 1 more-specific level\n",
+            )?
+            .try_stderr(is_empty())?
+        )
+    }}
+    annotate_coverage_text_only {|mut cmd| {
+        Ok(cmd
+            .args(["--annotate-coverage", "--output-type", "tsv"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("only supported for text output"))?
+        )
+    }}
+    profile {|mut cmd| {
+        Ok(cmd
+            .arg("--profile")
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(starts_with("Profile: read, parse and sort: "))?
+            .try_stderr(contains("Profile: validate: "))?
+            .try_stderr(contains("Profile: transform: "))?
+            .try_stderr(contains("Profile: output: "))?
+        )
+    }}
+    dedup_key_prefix_maxlength {|mut cmd| {
+        Ok(cmd
+            .args(["--dedup-key", "prefix-maxlength"])
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24-25\n192.0.2.0/24-26\n192.0.2.0/25\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24\n192.0.2.0/24-25\n192.0.2.0/24-26\n192.0.2.0/25\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    dedup_key_prefix {|mut cmd| {
+        Ok(cmd
+            .args(["--dedup-key", "prefix"])
+            .write_stdin("192.0.2.0/24\n192.0.2.0/24-25\n192.0.2.0/24-26\n192.0.2.0/25\n")
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.0/24-26\n192.0.2.0/25\n")?
+            .try_stderr(concat!(
+                "Warning: folded 192.0.2.0/24 into 192.0.2.0/24-26, changing the set of entries\n",
+                "Warning: folded 192.0.2.0/24-25 into 192.0.2.0/24-26, changing the set of entries\n",
+            ))?
+        )
+    }}
+    fold_maxlength_alias {|mut cmd| {
+        let input = "192.0.2.0/24\n192.0.2.0/24-25\n192.0.2.0/24-26\n192.0.2.0/25\n";
+        let expect = assert_cmd::cmd::Command::cargo_bin("roasort")?
+            .args(["--dedup-key", "prefix"])
+            .write_stdin(input)
+            .output()?;
+        Ok(cmd
+            .arg("--fold-maxlength")
+            .write_stdin(input)
+            .assert()
+            .try_success()?
+            .try_stdout(String::from_utf8(expect.stdout)?)?
+            .try_stderr(String::from_utf8(expect.stderr)?)?
+        )
+    }}
+    fold_maxlength_conflicts_with_dedup_key {|mut cmd| {
+        Ok(cmd
+            .args(["--fold-maxlength", "--dedup-key", "prefix-maxlength"])
+            .write_stdin("192.0.2.0/24\n")
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("cannot be used with"))?
+        )
+    }}
+    fix {|mut cmd| {
+        Ok(cmd
+            .arg("--fix")
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    #[cfg(feature = "json")]
+    fix_conflicts_with_validate {|mut cmd| {
+        Ok(cmd
+            .args(["--fix", "--validate"])
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("cannot be used with"))?
+        )
+    }}
+    fix_in_place {|mut cmd| {
+        let path = std::env::temp_dir()
+            .join(format!("roasort-fix-in-place-{}.txt", std::process::id()));
+        _ = std::fs::copy(ERR_TXT_PATH, &path)?;
+        let assert = cmd
+            .args(["--fix", "--in-place"])
+            .arg(&path)
+            .assert()
+            .try_success()?
+            .try_stdout(is_empty())?
+            .try_stderr(is_empty())?;
+        let rewritten = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(rewritten, std::fs::read_to_string(OK_TXT_PATH)?);
+        Ok(assert)
+    }}
+    in_place_requires_file {|mut cmd| {
+        Ok(cmd
+            .args(["--fix", "--in-place"])
+            .pipe_stdin(ERR_TXT_PATH)?
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("--in-place requires a text input file"))?
+        )
+    }}
+    roa_without_econtent {|mut cmd| {
+        Ok(cmd
+            .arg(NO_ECONTENT_ROA_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("ROA has no embedded eContent"))?
+        )
+    }}
+    sample_with_seed {|mut cmd| {
+        Ok(cmd
+            .args(["--sample", "3", "--seed", "42"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout("192.0.2.192/26\n2001:db8::/48\n2001:db8:1::/48-52\n")?
+            .try_stderr(is_empty())?
+        )
+    }}
+    sample_larger_than_input {|mut cmd| {
+        Ok(cmd
+            .args(["--sample", "100"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    output_to_file {|mut cmd| {
+        let path = std::env::temp_dir()
+            .join(format!("roasort-output-{}.txt", std::process::id()));
+        let assert = cmd
+            .args(["-o"])
+            .arg(&path)
+            .arg(ERR_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(starts_with(ERR_MSG))?;
+        let written = std::fs::read_to_string(&path)?;
+        std::fs::remove_file(&path)?;
+        assert_eq!(written, std::fs::read_to_string(OK_TXT_PATH)?);
+        Ok(assert)
+    }}
+    output_to_stdout {|mut cmd| {
+        Ok(cmd
+            .args(["--output", "-"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(eq_file(OK_TXT_PATH))?
+            .try_stderr(is_empty())?
+        )
+    }}
+    output_create_failure {|mut cmd| {
+        Ok(cmd
+            .args(["--output", "tests/data/missing/out.txt"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stdout(is_empty())?
+            .try_stderr(contains("failed to create output file tests/data/missing/out.txt"))?
+        )
+    }}
+    output_overwriting_input {|mut cmd| {
+        Ok(cmd
+            .args(["--output", OK_TXT_PATH])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_failure()?
+            .try_stderr(contains("output would overwrite the input file"))?
+        )
+    }}
+    #[cfg(feature = "json")]
+    json_output {|mut cmd| {
+        Ok(cmd
+            .args(["--output-type", "json", "--skip", "2", "--limit", "3"])
+            .arg(OK_TXT_PATH)
+            .assert()
+            .try_success()?
+            .try_stdout(concat!(
+                r#"[{"prefix":"192.0.2.128/25"},"#,
+                r#"{"prefix":"192.0.2.128/25","maxLength":26},"#,
+                r#"{"prefix":"192.0.2.128/25","maxLength":27}]"#,
+                "\n",
+            ))?
+            .try_stderr(is_empty())?
+        )
+    }}
 }
 
 macro_rules! cases {
-    ( $( $name:ident { $test:expr } )* ) => {
+    ( $( $(#[$attr:meta])* $name:ident { $test:expr } )* ) => {
         $(
+            $(#[$attr])*
             #[test]
             fn $name() -> Result<(), Box<dyn std::error::Error>> {
                 assert_cmd::cmd::Command::cargo_bin("roasort")
@@ -0,0 +1,29 @@
+use ip::concrete::Afi;
+
+use roasort::Roa;
+
+#[test]
+fn structured_access() -> anyhow::Result<()> {
+    let roa = Roa::from_der(include_bytes!("data/ok.roa"))?;
+    assert_eq!(roa.as_id()?, 65000);
+    let addresses = roa
+        .addresses()
+        .map(|entry| entry.map(|(afi, prefix, max_length)| (afi, prefix.to_string(), max_length)))
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    assert_eq!(addresses.len(), 12);
+    assert_eq!(addresses[0], (Afi::Ipv4, "192.0.2.0/24".to_string(), None));
+    assert_eq!(
+        addresses[3],
+        (Afi::Ipv4, "192.0.2.128/25".to_string(), Some(26))
+    );
+    assert_eq!(
+        addresses[11],
+        (Afi::Ipv6, "2001:db8:1:1::/64".to_string(), None)
+    );
+    Ok(())
+}
+
+#[test]
+fn not_a_roa() {
+    assert!(Roa::from_der(b"not a ROA").is_err());
+}